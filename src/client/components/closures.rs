@@ -0,0 +1,232 @@
+use crate::server::{db, import, schema::Closure};
+use chrono::{Local, NaiveDate};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+use std::error::Error;
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_closures() -> Vec<Closure> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_closures(&conn) {
+            Ok(closures) => closures,
+            Err(e) => {
+                error!("Failed to load closures: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn open_ics_file_dialog() -> Result<Option<String>, Box<dyn Error>> {
+    let file_handle = rfd::AsyncFileDialog::new()
+        .add_filter("Calendar", &["ics"])
+        .set_title("Import Closures from Calendar")
+        .pick_file()
+        .await;
+
+    match file_handle {
+        Some(handle) => {
+            info!("Reading calendar file: {:?}", handle.path());
+            let content = handle.read().await;
+            Ok(Some(String::from_utf8(content)?))
+        }
+        None => Ok(None),
+    }
+}
+
+#[component]
+pub fn Closures() -> Element {
+    let mut closures = use_signal(load_closures);
+    let mut new_date = use_signal(String::new);
+    let mut new_name = use_signal(String::new);
+    let mut closure_status = use_signal(|| None::<String>);
+    let mut is_importing = use_signal(|| false);
+
+    let handle_add = move |_| {
+        let Ok(date) = NaiveDate::parse_from_str(&new_date.read(), "%Y-%m-%d") else {
+            closure_status.set(Some("Pick a date".to_string()));
+            return;
+        };
+        let name = new_name.read().trim().to_string();
+        if name.is_empty() {
+            closure_status.set(Some("Enter a name for the closure".to_string()));
+            return;
+        }
+
+        let closure = Closure { id: 0, date, name };
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_closure(&conn, &closure) {
+                Ok(saved) => {
+                    info!("Added closure: {}", saved);
+                    closures.set(load_closures());
+                    new_date.set(String::new());
+                    new_name.set(String::new());
+                    closure_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save closure: {}", e);
+                    closure_status.set(Some(format!("Error saving closure: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                closure_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let handle_import = move |_| {
+        if *is_importing.read() {
+            return;
+        }
+        is_importing.set(true);
+        closure_status.set(None);
+
+        spawn(async move {
+            match open_ics_file_dialog().await {
+                Ok(Some(ics_data)) => match import::import_closures_from_ics(&ics_data) {
+                    Ok(imported) => match db::establish_connection() {
+                        Ok(conn) => match import::save_imported_closures(&conn, imported) {
+                            Ok(count) => {
+                                closures.set(load_closures());
+                                closure_status.set(Some(format!(
+                                    "Successfully imported {} closed dates",
+                                    count
+                                )));
+                            }
+                            Err(e) => {
+                                error!("Failed to save imported closures: {}", e);
+                                closure_status.set(Some(format!("Error saving closures: {}", e)));
+                            }
+                        },
+                        Err(e) => {
+                            error!("Failed to connect to database: {}", e);
+                            closure_status.set(Some(format!("Database connection error: {}", e)));
+                        }
+                    },
+                    Err(e) => {
+                        error!("Failed to parse calendar file: {}", e);
+                        closure_status.set(Some(format!("Invalid calendar file: {}", e)));
+                    }
+                },
+                Ok(None) => info!("Closure import cancelled by user"),
+                Err(e) => {
+                    error!("Error reading file: {}", e);
+                    closure_status.set(Some(format!("Error reading file: {}", e)));
+                }
+            }
+            is_importing.set(false);
+        });
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_closure(&conn, id) {
+            Ok(_) => closures.write().retain(|closure| closure.id != id),
+            Err(e) => {
+                error!("Failed to delete closure: {}", e);
+                closure_status.set(Some(format!("Error deleting closure: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            closure_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    // Past closures stay stored for the schedules they shaped, but are not listed
+    let today = Local::now().date_naive();
+    let upcoming: Vec<Closure> = closures
+        .read()
+        .iter()
+        .filter(|closure| closure.date >= today)
+        .cloned()
+        .collect();
+    let past_count = closures.read().len() - upcoming.len();
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Closures" }
+            p { class: "settings-hint", "Public holidays and other dates the office is shut. Nobody is scheduled on them; add them by hand or import an .ics calendar." }
+
+            if upcoming.is_empty() {
+                p { class: "settings-hint", "No upcoming closures." }
+            } else {
+                ul { class: "rule-list",
+                    for closure in upcoming {
+                        li { key: "{closure.id}", class: "rule-item",
+                            span { "{closure}" }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete closure",
+                                onclick: move |_| handle_delete(closure.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if past_count > 0 {
+                p { class: "settings-hint", "{past_count} past closed dates are not shown." }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "closure-date", "Date" }
+                    input {
+                        id: "closure-date",
+                        r#type: "date",
+                        value: "{new_date}",
+                        oninput: move |evt: FormEvent| new_date.set(evt.value())
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "closure-name", "Name" }
+                    input {
+                        id: "closure-name",
+                        class: "team-name-input",
+                        r#type: "text",
+                        value: "{new_name}",
+                        oninput: move |evt: FormEvent| new_name.set(evt.value())
+                    }
+                }
+            }
+
+            div { class: "settings-buttons",
+                button {
+                    class: "button primary",
+                    onclick: handle_add,
+                    "Add Closure"
+                }
+                button {
+                    class: "button secondary",
+                    disabled: *is_importing.read(),
+                    onclick: handle_import,
+                    if *is_importing.read() { "Importing..." } else { "Import .ics" }
+                }
+            }
+
+            if let Some(status) = closure_status.read().as_ref() {
+                div {
+                    class: if status.starts_with("Successfully") { "settings-status success" } else { "settings-status error" },
+                    "{status}"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::server::{
+    db,
+    schema::{CoverageRule, Role, SchedulerConfig, Weekday},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_rules() -> Vec<CoverageRule> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_coverage_rules(&conn) {
+            Ok(rules) => rules,
+            Err(e) => {
+                error!("Failed to load coverage rules: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_work_week() -> Vec<Weekday> {
+    match db::establish_connection() {
+        Ok(conn) => match db::load_scheduler_config(&conn) {
+            Ok(config) => config.work_week(),
+            Err(e) => {
+                error!("Failed to load scheduler config: {}", e);
+                SchedulerConfig::default().work_week()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            SchedulerConfig::default().work_week()
+        }
+    }
+}
+
+fn empty_rule() -> CoverageRule {
+    CoverageRule {
+        id: 0,
+        role: Role::ITSupport,
+        min_count: Some(1),
+        max_count: None,
+        days: Vec::new(),
+    }
+}
+
+#[component]
+pub fn CoverageRules() -> Element {
+    let mut rules = use_signal(load_rules);
+    let work_week = use_signal(load_work_week);
+    let mut new_rule = use_signal(empty_rule);
+    let mut rule_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let rule = new_rule.read().clone();
+        if rule.min_count.is_none() && rule.max_count.is_none() {
+            rule_status.set(Some("Set a minimum or a maximum for the rule".to_string()));
+            return;
+        }
+        if let (Some(min), Some(max)) = (rule.min_count, rule.max_count) {
+            if min > max {
+                rule_status.set(Some("Minimum cannot be greater than maximum".to_string()));
+                return;
+            }
+        }
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_coverage_rule(&conn, &rule) {
+                Ok(saved) => {
+                    info!("Added coverage rule: {}", saved);
+                    rules.write().push(saved);
+                    new_rule.set(empty_rule());
+                    rule_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save coverage rule: {}", e);
+                    rule_status.set(Some(format!("Error saving rule: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                rule_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_coverage_rule(&conn, id) {
+            Ok(_) => rules.write().retain(|rule| rule.id != id),
+            Err(e) => {
+                error!("Failed to delete coverage rule: {}", e);
+                rule_status.set(Some(format!("Error deleting rule: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            rule_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let mut toggle_day = move |day: Weekday| {
+        let mut rule = new_rule.write();
+        if let Some(pos) = rule.days.iter().position(|d| d == &day) {
+            rule.days.remove(pos);
+        } else {
+            rule.days.push(day);
+        }
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Role Coverage Rules" }
+            p { class: "settings-hint", "Minimum and maximum number of people per role in the office. No days selected means every day." }
+
+            if rules.read().is_empty() {
+                p { class: "settings-hint", "No coverage rules yet." }
+            } else {
+                ul { class: "rule-list",
+                    for rule in rules.read().iter().cloned() {
+                        li { key: "{rule.id}", class: "rule-item",
+                            span { "{rule}" }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete rule",
+                                onclick: move |_| handle_delete(rule.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "rule-role", "Role" }
+                    select {
+                        id: "rule-role",
+                        class: "rule-role-select",
+                        onchange: move |evt: FormEvent| {
+                            if let Some(role) = evt.value().parse::<usize>().ok().and_then(|i| Role::values().get(i)) {
+                                new_rule.write().role = role.clone();
+                            }
+                        },
+                        for (index, role) in Role::values().iter().enumerate() {
+                            option { value: "{index}", selected: new_rule.read().role == *role, "{role}" }
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "rule-min", "At least" }
+                    input {
+                        id: "rule-min",
+                        r#type: "number",
+                        min: "0",
+                        value: new_rule.read().min_count.map(|c| c.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| new_rule.write().min_count = evt.value().parse().ok()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "rule-max", "At most" }
+                    input {
+                        id: "rule-max",
+                        r#type: "number",
+                        min: "0",
+                        value: new_rule.read().max_count.map(|c| c.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| new_rule.write().max_count = evt.value().parse().ok()
+                    }
+                }
+            }
+
+            div { class: "checkbox-row",
+                for day in work_week.read().iter() {
+                    {
+                        let day_key = day.clone();
+                        rsx! {
+                            label { class: "day-checkbox",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: new_rule.read().days.contains(day),
+                                    onclick: move |_| toggle_day(day_key.clone())
+                                }
+                                "{day}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Rule"
+            }
+
+            if let Some(status) = rule_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::server::{
+    db,
+    schema::{Desk, DeskAttribute, Location},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_desks() -> Vec<Desk> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_desks(&conn) {
+            Ok(desks) => desks,
+            Err(e) => {
+                error!("Failed to load desks: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_locations() -> Vec<Location> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_locations(&conn) {
+            Ok(locations) => locations,
+            Err(e) => {
+                error!("Failed to load locations: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn empty_desk() -> Desk {
+    Desk {
+        id: 0,
+        name: String::new(),
+        floor: String::new(),
+        zone: String::new(),
+        location_id: None,
+        attributes: Vec::new(),
+    }
+}
+
+#[component]
+pub fn Desks() -> Element {
+    let mut desks = use_signal(load_desks);
+    let locations = use_signal(load_locations);
+    let mut new_desk = use_signal(empty_desk);
+    let mut desk_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let mut desk = new_desk.read().clone();
+        desk.name = desk.name.trim().to_string();
+        desk.floor = desk.floor.trim().to_string();
+        desk.zone = desk.zone.trim().to_string();
+        if desk.name.is_empty() || desk.floor.is_empty() || desk.zone.is_empty() {
+            desk_status.set(Some("Enter a desk name, floor and zone".to_string()));
+            return;
+        }
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_desk(&conn, &desk) {
+                Ok(saved) => {
+                    info!("Added desk: {}", saved);
+                    desks.set(load_desks());
+                    // Keeps floor, zone and location for adding the next desk nearby
+                    new_desk.write().name = String::new();
+                    desk_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save desk: {}", e);
+                    desk_status.set(Some(format!("Error saving desk: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                desk_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_desk(&conn, id) {
+            Ok(_) => desks.write().retain(|desk| desk.id != id),
+            Err(e) => {
+                error!("Failed to delete desk: {}", e);
+                desk_status.set(Some(format!("Error deleting desk: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            desk_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let location_name = move |desk: &Desk| {
+        desk.location_id.and_then(|id| {
+            locations
+                .read()
+                .iter()
+                .find(|location| location.id == id)
+                .map(|location| location.name.clone())
+        })
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Desks" }
+            p { class: "settings-hint", "Once office days are set, everyone is given a desk for the week, near their teammates and with the equipment marked on their employee record where possible." }
+
+            if desks.read().is_empty() {
+                p { class: "settings-hint", "No desks yet. Schedules are made without desk assignments." }
+            } else {
+                ul { class: "rule-list",
+                    for desk in desks.read().iter().cloned() {
+                        li { key: "{desk.id}", class: "rule-item",
+                            span {
+                                "{desk}"
+                                if let Some(name) = location_name(&desk) { " @ {name}" }
+                            }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete desk",
+                                onclick: move |_| handle_delete(desk.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "desk-name", "Desk" }
+                    input {
+                        id: "desk-name",
+                        class: "team-name-input",
+                        r#type: "text",
+                        value: "{new_desk.read().name}",
+                        oninput: move |evt: FormEvent| new_desk.write().name = evt.value()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "desk-floor", "Floor" }
+                    input {
+                        id: "desk-floor",
+                        r#type: "text",
+                        value: "{new_desk.read().floor}",
+                        oninput: move |evt: FormEvent| new_desk.write().floor = evt.value()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "desk-zone", "Zone" }
+                    input {
+                        id: "desk-zone",
+                        r#type: "text",
+                        value: "{new_desk.read().zone}",
+                        oninput: move |evt: FormEvent| new_desk.write().zone = evt.value()
+                    }
+                }
+                if !locations.read().is_empty() {
+                    div { class: "settings-field",
+                        label { r#for: "desk-location", "Location" }
+                        select {
+                            id: "desk-location",
+                            onchange: move |evt: FormEvent| new_desk.write().location_id = evt.value().parse().ok(),
+                            option { value: "", selected: new_desk.read().location_id.is_none(), "Any" }
+                            for location in locations.read().iter() {
+                                option { value: "{location.id}", selected: new_desk.read().location_id == Some(location.id), "{location.name}" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "checkbox-row",
+                for attribute in DeskAttribute::values() {
+                    {
+                        let attribute_key = attribute.clone();
+                        rsx! {
+                            label { class: "day-checkbox",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: new_desk.read().attributes.contains(attribute),
+                                    onclick: move |_| {
+                                        let mut desk = new_desk.write();
+                                        if desk.attributes.contains(&attribute_key) {
+                                            desk.attributes.retain(|a| *a != attribute_key);
+                                        } else {
+                                            desk.attributes.push(attribute_key.clone());
+                                        }
+                                    }
+                                }
+                                "{attribute}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Desk"
+            }
+
+            if let Some(status) = desk_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
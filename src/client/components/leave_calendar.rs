@@ -0,0 +1,241 @@
+use crate::server::{
+    db,
+    schema::{Leave, LeaveKind},
+};
+use chrono::{Datelike, Local, NaiveDate};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const LEAVE_CALENDAR_CSS: Asset = asset!("/assets/styles/leave_calendar.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_leave(employee_id: usize) -> Vec<Leave> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_leave_for_employee(&conn, employee_id) {
+            Ok(periods) => periods,
+            Err(e) => {
+                error!("Failed to load leave: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn kind_class(kind: &LeaveKind) -> &'static str {
+    match kind {
+        LeaveKind::Annual => "annual",
+        LeaveKind::Sick => "sick",
+        LeaveKind::Training => "training",
+    }
+}
+
+// Leave periods of one employee, with a month calendar and a form to add more
+#[component]
+pub fn LeaveCalendar(employee_id: usize) -> Element {
+    let mut periods = use_signal(move || load_leave(employee_id));
+    let today = Local::now().date_naive();
+    let mut shown_month = use_signal(move || (today.year(), today.month()));
+    let mut new_kind = use_signal(|| LeaveKind::Annual);
+    let mut new_start = use_signal(String::new);
+    let mut new_end = use_signal(String::new);
+    let mut leave_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let Ok(start) = NaiveDate::parse_from_str(&new_start.read(), "%Y-%m-%d") else {
+            leave_status.set(Some("Pick a start date".to_string()));
+            return;
+        };
+        // A single day needs no end date
+        let end = NaiveDate::parse_from_str(&new_end.read(), "%Y-%m-%d").unwrap_or(start);
+        if end < start {
+            leave_status.set(Some("The end date is before the start date".to_string()));
+            return;
+        }
+
+        let leave = Leave {
+            id: 0,
+            employee_id,
+            kind: new_kind.read().clone(),
+            start,
+            end,
+        };
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_leave(&conn, &leave) {
+                Ok(saved) => {
+                    info!("Added leave: {}", saved);
+                    periods.write().push(saved);
+                    periods.write().sort_by_key(|period| period.start);
+                    shown_month.set((start.year(), start.month()));
+                    new_start.set(String::new());
+                    new_end.set(String::new());
+                    leave_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save leave: {}", e);
+                    leave_status.set(Some(format!("Error saving leave: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                leave_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_leave(&conn, id) {
+            Ok(_) => periods.write().retain(|period| period.id != id),
+            Err(e) => {
+                error!("Failed to delete leave: {}", e);
+                leave_status.set(Some(format!("Error deleting leave: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            leave_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let (year, month) = shown_month();
+    let previous_month = if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    };
+    let next_month = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(today);
+    let leading_blanks = first.weekday().num_days_from_monday();
+    let days: Vec<(NaiveDate, Option<Leave>)> = first
+        .iter_days()
+        .take_while(|date| date.month() == month)
+        .map(|date| {
+            let period = periods.read().iter().find(|p| p.covers(date)).cloned();
+            (date, period)
+        })
+        .collect();
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: LEAVE_CALENDAR_CSS,
+        }
+        div { class: "leave-calendar",
+            h3 { "Leave" }
+
+            div { class: "leave-calendar-nav",
+                button {
+                    class: "btn btn-secondary",
+                    onclick: move |_| shown_month.set(previous_month),
+                    "‹"
+                }
+                span { class: "leave-calendar-month", {first.format("%B %Y").to_string()} }
+                button {
+                    class: "btn btn-secondary",
+                    onclick: move |_| shown_month.set(next_month),
+                    "›"
+                }
+            }
+
+            div { class: "leave-calendar-grid",
+                for name in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] {
+                    div { class: "leave-calendar-weekday", "{name}" }
+                }
+                for index in 0..leading_blanks {
+                    div { key: "blank-{index}", class: "leave-calendar-day blank" }
+                }
+                for (date, period) in days {
+                    div {
+                        key: "{date}",
+                        class: format!(
+                            "leave-calendar-day {} {}",
+                            period.as_ref().map_or("", |p| kind_class(&p.kind)),
+                            if date.weekday().num_days_from_monday() >= 5 { "weekend" } else { "" }
+                        ),
+                        title: period.as_ref().map(|p| p.to_string()).unwrap_or_default(),
+                        "{date.day()}"
+                    }
+                }
+            }
+
+            div { class: "leave-legend",
+                for kind in LeaveKind::values() {
+                    span { class: "leave-legend-item",
+                        span { class: format!("leave-swatch {}", kind_class(kind)) }
+                        "{kind}"
+                    }
+                }
+            }
+
+            if periods.read().is_empty() {
+                p { class: "leave-hint", "No leave recorded." }
+            } else {
+                ul { class: "leave-list",
+                    for period in periods.read().iter().cloned() {
+                        li { key: "{period.id}", class: "leave-item",
+                            span { "{period}" }
+                            button {
+                                class: "leave-delete",
+                                title: "Delete leave",
+                                onclick: move |_| handle_delete(period.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "leave-form",
+                select {
+                    class: "form-control",
+                    onchange: move |evt: FormEvent| {
+                        new_kind.set(match evt.value().as_str() {
+                            "Sick" => LeaveKind::Sick,
+                            "Training" => LeaveKind::Training,
+                            _ => LeaveKind::Annual,
+                        });
+                    },
+                    for kind in LeaveKind::values() {
+                        option { value: "{kind}", selected: *new_kind.read() == *kind, "{kind}" }
+                    }
+                }
+                input {
+                    class: "form-control",
+                    r#type: "date",
+                    title: "First day",
+                    value: "{new_start}",
+                    oninput: move |evt: FormEvent| new_start.set(evt.value())
+                }
+                input {
+                    class: "form-control",
+                    r#type: "date",
+                    title: "Last day (leave empty for a single day)",
+                    value: "{new_end}",
+                    oninput: move |evt: FormEvent| new_end.set(evt.value())
+                }
+                button {
+                    class: "btn btn-primary",
+                    onclick: handle_add,
+                    "Add Leave"
+                }
+            }
+
+            if let Some(status) = leave_status.read().as_ref() {
+                p { class: "leave-status", "{status}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::server::{db, schema::Location};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_locations() -> Vec<Location> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_locations(&conn) {
+            Ok(locations) => locations,
+            Err(e) => {
+                error!("Failed to load locations: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn empty_location() -> Location {
+    Location {
+        id: 0,
+        name: String::new(),
+        capacity: None,
+    }
+}
+
+#[component]
+pub fn Locations() -> Element {
+    let mut locations = use_signal(load_locations);
+    let mut new_location = use_signal(empty_location);
+    let mut location_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let mut location = new_location.read().clone();
+        location.name = location.name.trim().to_string();
+        if location.name.is_empty() {
+            location_status.set(Some("Enter a location name".to_string()));
+            return;
+        }
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_location(&conn, &location) {
+                Ok(saved) => {
+                    info!("Added location: {}", saved);
+                    locations.write().push(saved);
+                    locations.write().sort_by(|a, b| a.name.cmp(&b.name));
+                    new_location.set(empty_location());
+                    location_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save location: {}", e);
+                    location_status.set(Some(format!("Error saving location: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                location_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_location(&conn, id) {
+            Ok(_) => locations.write().retain(|location| location.id != id),
+            Err(e) => {
+                error!("Failed to delete location: {}", e);
+                location_status.set(Some(format!("Error deleting location: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            location_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Locations" }
+            p { class: "settings-hint", "Offices with their own desks. Each day's headcount is capped and balanced per office; set people's home office from the Employees page." }
+
+            if locations.read().is_empty() {
+                p { class: "settings-hint", "No locations yet. Everyone shares the limits under Office Capacity." }
+            } else {
+                ul { class: "rule-list",
+                    for location in locations.read().iter().cloned() {
+                        li { key: "{location.id}", class: "rule-item",
+                            span { "{location}" }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete location",
+                                onclick: move |_| handle_delete(location.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "location-name", "Name" }
+                    input {
+                        id: "location-name",
+                        class: "team-name-input",
+                        r#type: "text",
+                        value: "{new_location.read().name}",
+                        oninput: move |evt: FormEvent| new_location.write().name = evt.value()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "location-capacity", "Desks" }
+                    input {
+                        id: "location-capacity",
+                        r#type: "number",
+                        min: "0",
+                        placeholder: "no limit",
+                        value: new_location.read().capacity.map(|c| c.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| new_location.write().capacity = evt.value().parse().ok()
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Location"
+            }
+
+            if let Some(status) = location_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::server::{
+    db,
+    schema::{Location, MeetingRoom},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_meeting_rooms() -> Vec<MeetingRoom> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_meeting_rooms(&conn) {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                error!("Failed to load meeting rooms: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_locations() -> Vec<Location> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_locations(&conn) {
+            Ok(locations) => locations,
+            Err(e) => {
+                error!("Failed to load locations: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn empty_meeting_room() -> MeetingRoom {
+    MeetingRoom {
+        id: 0,
+        name: String::new(),
+        capacity: 0,
+        location_id: None,
+    }
+}
+
+#[component]
+pub fn MeetingRooms() -> Element {
+    let mut rooms = use_signal(load_meeting_rooms);
+    let locations = use_signal(load_locations);
+    let mut new_room = use_signal(empty_meeting_room);
+    let mut room_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let mut room = new_room.read().clone();
+        room.name = room.name.trim().to_string();
+        if room.name.is_empty() || room.capacity == 0 {
+            room_status.set(Some("Enter a room name and how many it seats".to_string()));
+            return;
+        }
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_meeting_room(&conn, &room) {
+                Ok(saved) => {
+                    info!("Added meeting room: {}", saved);
+                    rooms.write().push(saved);
+                    rooms.write().sort_by(|a, b| a.name.cmp(&b.name));
+                    new_room.set(empty_meeting_room());
+                    room_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save meeting room: {}", e);
+                    room_status.set(Some(format!("Error saving meeting room: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                room_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_meeting_room(&conn, id) {
+            Ok(_) => rooms.write().retain(|room| room.id != id),
+            Err(e) => {
+                error!("Failed to delete meeting room: {}", e);
+                room_status.set(Some(format!("Error deleting meeting room: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            room_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let location_name = move |room: &MeetingRoom| {
+        room.location_id.and_then(|id| {
+            locations
+                .read()
+                .iter()
+                .find(|location| location.id == id)
+                .map(|location| location.name.clone())
+        })
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Meeting Rooms" }
+            p { class: "settings-hint", "Rooms that can be booked from the Schedules page. Bookings are checked against who is in the office that day." }
+
+            if rooms.read().is_empty() {
+                p { class: "settings-hint", "No meeting rooms yet." }
+            } else {
+                ul { class: "rule-list",
+                    for room in rooms.read().iter().cloned() {
+                        li { key: "{room.id}", class: "rule-item",
+                            span {
+                                "{room}"
+                                if let Some(name) = location_name(&room) { " @ {name}" }
+                            }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete meeting room and its bookings",
+                                onclick: move |_| handle_delete(room.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "room-name", "Room" }
+                    input {
+                        id: "room-name",
+                        class: "team-name-input",
+                        r#type: "text",
+                        value: "{new_room.read().name}",
+                        oninput: move |evt: FormEvent| new_room.write().name = evt.value()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "room-capacity", "Seats" }
+                    input {
+                        id: "room-capacity",
+                        r#type: "number",
+                        min: "1",
+                        value: if new_room.read().capacity > 0 { new_room.read().capacity.to_string() } else { String::new() },
+                        oninput: move |evt: FormEvent| new_room.write().capacity = evt.value().parse().unwrap_or(0)
+                    }
+                }
+                if !locations.read().is_empty() {
+                    div { class: "settings-field",
+                        label { r#for: "room-location", "Location" }
+                        select {
+                            id: "room-location",
+                            onchange: move |evt: FormEvent| new_room.write().location_id = evt.value().parse().ok(),
+                            option { value: "", selected: new_room.read().location_id.is_none(), "Any" }
+                            for location in locations.read().iter() {
+                                option { value: "{location.id}", selected: new_room.read().location_id == Some(location.id), "{location.name}" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Meeting Room"
+            }
+
+            if let Some(status) = room_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
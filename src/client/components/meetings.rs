@@ -0,0 +1,314 @@
+use crate::server::{
+    db,
+    meetings::{booking_warnings, suggest_meeting_days},
+    schema::{Booking, DatedSchedule, Employee, EmployeeId, MeetingRoom},
+};
+use chrono::{Datelike, Local, NaiveDate, NaiveTime};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const MEETINGS_CSS: Asset = asset!("/assets/styles/meetings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+// Best days offered for the invitees
+const SUGGESTION_COUNT: usize = 3;
+
+fn load_meeting_rooms() -> Vec<MeetingRoom> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_meeting_rooms(&conn) {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                error!("Failed to load meeting rooms: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_bookings(year: i32, month: u32) -> Vec<Booking> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_bookings_for_month(&conn, year, month) {
+            Ok(bookings) => bookings,
+            Err(e) => {
+                error!("Failed to load bookings: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Room bookings of one month, checked against the month's schedule
+#[component]
+pub fn Meetings(dated: DatedSchedule, employees: Vec<Employee>, year: i32, month: u32) -> Element {
+    let rooms = use_signal(load_meeting_rooms);
+    let mut bookings = use_signal(move || load_bookings(year, month));
+    let mut new_room = use_signal(|| None::<usize>);
+    let mut new_title = use_signal(String::new);
+    let mut new_date = use_signal(String::new);
+    let mut new_start = use_signal(|| "09:00".to_string());
+    let mut new_end = use_signal(|| "10:00".to_string());
+    let mut new_attendees: Signal<Vec<EmployeeId>> = use_signal(Vec::new);
+    let mut booking_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let Some(room_id) = new_room() else {
+            booking_status.set(Some("Pick a room".to_string()));
+            return;
+        };
+        let title = new_title.read().trim().to_string();
+        if title.is_empty() {
+            booking_status.set(Some("Enter a title".to_string()));
+            return;
+        }
+        let date = match NaiveDate::parse_from_str(&new_date.read(), "%Y-%m-%d") {
+            Ok(date) if date.year() == year && date.month() == month => date,
+            _ => {
+                booking_status.set(Some("Pick a date in this month".to_string()));
+                return;
+            }
+        };
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&new_start.read(), "%H:%M"),
+            NaiveTime::parse_from_str(&new_end.read(), "%H:%M"),
+        ) else {
+            booking_status.set(Some("Enter a start and end time".to_string()));
+            return;
+        };
+        if end <= start {
+            booking_status.set(Some("The meeting ends before it starts".to_string()));
+            return;
+        }
+        if new_attendees.read().is_empty() {
+            booking_status.set(Some("Invite at least one person".to_string()));
+            return;
+        }
+
+        let booking = Booking {
+            id: 0,
+            room_id,
+            title,
+            date,
+            start,
+            end,
+            attendees: new_attendees.read().clone(),
+        };
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_booking(&conn, &booking) {
+                Ok(saved) => {
+                    info!("Added booking: {}", saved);
+                    bookings.write().push(saved);
+                    bookings.write().sort_by_key(|b| (b.date, b.start));
+                    // Room and time are kept for booking a series of meetings
+                    new_title.set(String::new());
+                    new_attendees.set(Vec::new());
+                    booking_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save booking: {}", e);
+                    booking_status.set(Some(format!("Error saving booking: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                booking_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_booking(&conn, id) {
+            Ok(_) => bookings.write().retain(|booking| booking.id != id),
+            Err(e) => {
+                error!("Failed to delete booking: {}", e);
+                booking_status.set(Some(format!("Error deleting booking: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            booking_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let warnings = booking_warnings(&bookings.read(), &rooms.read(), &employees, &dated);
+    let room_name = |booking: &Booking| {
+        rooms
+            .read()
+            .iter()
+            .find(|room| room.id == booking.room_id)
+            .map(|room| room.name.clone())
+            .unwrap_or_default()
+    };
+
+    // Days already gone are not worth suggesting
+    let today = Local::now().date_naive();
+    let selected_room = rooms
+        .read()
+        .iter()
+        .find(|room| Some(room.id) == new_room())
+        .cloned();
+    let suggestions: Vec<_> = suggest_meeting_days(
+        &dated,
+        &employees,
+        &new_attendees.read(),
+        selected_room.as_ref(),
+    )
+    .into_iter()
+    .filter(|day| day.date >= today && day.present > 0)
+    .take(SUGGESTION_COUNT)
+    .collect();
+    let invited = new_attendees.read().len();
+
+    let mut sorted_employees = employees.clone();
+    sorted_employees.sort_by(|a, b| a.name.cmp(&b.name));
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1);
+    let last_day =
+        first_day.and_then(|first| first.iter_days().take_while(|d| d.month() == month).last());
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: MEETINGS_CSS,
+        }
+        div { class: "meetings",
+            h3 { "Meetings" }
+
+            if rooms.read().is_empty() {
+                p { class: "meeting-hint", "Add meeting rooms in Settings to book meetings here." }
+            } else {
+                if !warnings.is_empty() {
+                    div { class: "error-message schedule-warnings",
+                        ul {
+                            for (index, warning) in warnings.iter().enumerate() {
+                                li { key: "{index}", "{warning}" }
+                            }
+                        }
+                    }
+                }
+
+                if bookings.read().is_empty() {
+                    p { class: "meeting-hint", "No meetings booked this month." }
+                } else {
+                    ul { class: "meeting-list",
+                        for booking in bookings.read().iter().cloned() {
+                            li { key: "{booking.id}", class: "meeting-item",
+                                span {
+                                    "{booking}"
+                                    span { class: "meeting-room-name", "{room_name(&booking)}" }
+                                    span { class: "meeting-attendee-count", "{booking.attendees.len()} invited" }
+                                }
+                                button {
+                                    class: "meeting-delete",
+                                    title: "Cancel meeting",
+                                    onclick: move |_| handle_delete(booking.id),
+                                    img {
+                                        src: DELETE_ICON,
+                                        width: "20",
+                                        height: "20",
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div { class: "meeting-form",
+                    select {
+                        class: "form-control",
+                        onchange: move |evt: FormEvent| new_room.set(evt.value().parse().ok()),
+                        option { value: "", selected: new_room().is_none(), "Room..." }
+                        for room in rooms.read().iter() {
+                            option { value: "{room.id}", selected: new_room() == Some(room.id), "{room}" }
+                        }
+                    }
+                    input {
+                        class: "form-control",
+                        r#type: "text",
+                        placeholder: "Title",
+                        value: "{new_title}",
+                        oninput: move |evt: FormEvent| new_title.set(evt.value())
+                    }
+                    input {
+                        class: "form-control",
+                        r#type: "date",
+                        min: first_day.map(|d| d.to_string()).unwrap_or_default(),
+                        max: last_day.map(|d| d.to_string()).unwrap_or_default(),
+                        value: "{new_date}",
+                        oninput: move |evt: FormEvent| new_date.set(evt.value())
+                    }
+                    input {
+                        class: "form-control",
+                        r#type: "time",
+                        title: "Start",
+                        value: "{new_start}",
+                        oninput: move |evt: FormEvent| new_start.set(evt.value())
+                    }
+                    input {
+                        class: "form-control",
+                        r#type: "time",
+                        title: "End",
+                        value: "{new_end}",
+                        oninput: move |evt: FormEvent| new_end.set(evt.value())
+                    }
+                    button {
+                        class: "btn btn-primary",
+                        onclick: handle_add,
+                        "Book"
+                    }
+                }
+
+                div { class: "attendee-grid",
+                    for employee in sorted_employees {
+                        label { key: "{employee.id}",
+                            input {
+                                r#type: "checkbox",
+                                checked: new_attendees.read().contains(&employee.id),
+                                onclick: move |_| {
+                                    let mut attendees = new_attendees.write();
+                                    if attendees.contains(&employee.id) {
+                                        attendees.retain(|id| *id != employee.id);
+                                    } else {
+                                        attendees.push(employee.id);
+                                    }
+                                }
+                            }
+                            "{employee.name}"
+                        }
+                    }
+                }
+
+                if invited > 0 {
+                    div { class: "meeting-suggestions",
+                        if suggestions.is_empty() {
+                            "None of the invitees are in on the days left this month."
+                        } else {
+                            "Best days:"
+                            for day in suggestions {
+                                button {
+                                    key: "{day.date}",
+                                    class: if new_date() == day.date.to_string() { "meeting-suggestion selected" } else { "meeting-suggestion" },
+                                    title: if day.missing.is_empty() { "Everyone is in".to_string() } else { format!("Missing: {}", day.missing.join(", ")) },
+                                    onclick: move |_| new_date.set(day.date.to_string()),
+                                    {format!("{} · {} of {} in", day.date.format("%a %-d %b"), day.present, invited)}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(status) = booking_status.read().as_ref() {
+                p { class: "meeting-status", "{status}" }
+            }
+        }
+    }
+}
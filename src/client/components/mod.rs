@@ -1,11 +1,31 @@
+pub mod closures;
+pub mod coverage_rules;
+pub mod desks;
 pub mod import_button;
+pub mod leave_calendar;
+pub mod locations;
+pub mod meeting_rooms;
+pub mod meetings;
 pub mod navbar;
 pub mod page_not_found;
+pub mod pair_constraints;
+pub mod scheduler_settings;
 pub mod searchbar;
 pub mod share;
+pub mod teams;
 
+pub use closures::Closures;
+pub use coverage_rules::CoverageRules;
+pub use desks::Desks;
 pub use import_button::ImportButton;
+pub use leave_calendar::LeaveCalendar;
+pub use locations::Locations;
+pub use meeting_rooms::MeetingRooms;
+pub use meetings::Meetings;
 pub use navbar::NavBar;
 pub use page_not_found::PageNotFound;
+pub use pair_constraints::PairConstraints;
+pub use scheduler_settings::SchedulerSettings;
 pub use searchbar::SearchBar;
 pub use share::ShareButton;
+pub use teams::Teams;
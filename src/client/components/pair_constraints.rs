@@ -0,0 +1,247 @@
+use crate::server::{
+    db,
+    schema::{Employee, PairConstraint, PairKind, SchedulerConfig, Weekday},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_pairs() -> Vec<PairConstraint> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_pair_constraints(&conn) {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                error!("Failed to load pair constraints: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_employees() -> Vec<Employee> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_employees(&conn) {
+            Ok(mut employees) => {
+                employees.sort_by(|a, b| a.name.cmp(&b.name));
+                employees
+            }
+            Err(e) => {
+                error!("Failed to load employees: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_work_week() -> Vec<Weekday> {
+    match db::establish_connection() {
+        Ok(conn) => match db::load_scheduler_config(&conn) {
+            Ok(config) => config.work_week(),
+            Err(e) => {
+                error!("Failed to load scheduler config: {}", e);
+                SchedulerConfig::default().work_week()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            SchedulerConfig::default().work_week()
+        }
+    }
+}
+
+// Employee ids start at 1, so 0 means nobody is selected yet
+fn empty_pair() -> PairConstraint {
+    PairConstraint {
+        id: 0,
+        first_id: 0,
+        second_id: 0,
+        kind: PairKind::Together { min_days: 1 },
+        first_name: String::new(),
+        second_name: String::new(),
+    }
+}
+
+#[component]
+pub fn PairConstraints() -> Element {
+    let mut pairs = use_signal(load_pairs);
+    let employees = use_signal(load_employees);
+    let mut new_pair = use_signal(empty_pair);
+    // A pair cannot share more days than the office is open
+    let work_days = use_signal(|| load_work_week().len());
+    let mut pair_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let mut pair = new_pair.read().clone();
+        if pair.first_id == 0 || pair.second_id == 0 {
+            pair_status.set(Some("Select both employees".to_string()));
+            return;
+        }
+        if pair.first_id == pair.second_id {
+            pair_status.set(Some("Select two different employees".to_string()));
+            return;
+        }
+
+        let name_of = |id: usize| {
+            employees
+                .read()
+                .iter()
+                .find(|e| e.id == id)
+                .map(|e| e.name.clone())
+                .unwrap_or_default()
+        };
+        pair.first_name = name_of(pair.first_id);
+        pair.second_name = name_of(pair.second_id);
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_pair_constraint(&conn, &pair) {
+                Ok(saved) => {
+                    info!("Added pair constraint: {}", saved);
+                    pairs.write().push(saved);
+                    new_pair.set(empty_pair());
+                    pair_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save pair constraint: {}", e);
+                    pair_status.set(Some(format!("Error saving pair: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                pair_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_pair_constraint(&conn, id) {
+            Ok(_) => pairs.write().retain(|pair| pair.id != id),
+            Err(e) => {
+                error!("Failed to delete pair constraint: {}", e);
+                pair_status.set(Some(format!("Error deleting pair: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            pair_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    let min_days = match new_pair.read().kind {
+        PairKind::Together { min_days } => Some(min_days),
+        PairKind::Apart => None,
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Pairs" }
+            p { class: "settings-hint", "Keep two people in the office together, such as a mentor and mentee, or never on the same day, such as two people sharing a workstation." }
+
+            if pairs.read().is_empty() {
+                p { class: "settings-hint", "No pairs yet." }
+            } else {
+                ul { class: "rule-list",
+                    for pair in pairs.read().iter().cloned() {
+                        li { key: "{pair.id}", class: "rule-item",
+                            span { "{pair}" }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete pair",
+                                onclick: move |_| handle_delete(pair.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "pair-first", "First employee" }
+                    select {
+                        id: "pair-first",
+                        class: "rule-role-select",
+                        onchange: move |evt: FormEvent| new_pair.write().first_id = evt.value().parse().unwrap_or(0),
+                        option { value: "0", selected: new_pair.read().first_id == 0, "Select..." }
+                        for employee in employees.read().iter() {
+                            option { value: "{employee.id}", selected: new_pair.read().first_id == employee.id, "{employee.name}" }
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "pair-second", "Second employee" }
+                    select {
+                        id: "pair-second",
+                        class: "rule-role-select",
+                        onchange: move |evt: FormEvent| new_pair.write().second_id = evt.value().parse().unwrap_or(0),
+                        option { value: "0", selected: new_pair.read().second_id == 0, "Select..." }
+                        for employee in employees.read().iter() {
+                            option { value: "{employee.id}", selected: new_pair.read().second_id == employee.id, "{employee.name}" }
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "pair-kind", "Rule" }
+                    select {
+                        id: "pair-kind",
+                        onchange: move |evt: FormEvent| {
+                            new_pair.write().kind = if evt.value() == "apart" {
+                                PairKind::Apart
+                            } else {
+                                PairKind::Together { min_days: 1 }
+                            };
+                        },
+                        option { value: "together", selected: min_days.is_some(), "Together" }
+                        option { value: "apart", selected: min_days.is_none(), "Never together" }
+                    }
+                }
+                if let Some(days) = min_days {
+                    div { class: "settings-field",
+                        label { r#for: "pair-min-days", "On at least (days)" }
+                        input {
+                            id: "pair-min-days",
+                            r#type: "number",
+                            min: "1",
+                            max: "{work_days}",
+                            value: "{days}",
+                            oninput: move |evt: FormEvent| {
+                                if let Ok(days) = evt.value().parse::<usize>() {
+                                    new_pair.write().kind = PairKind::Together { min_days: days.clamp(1, work_days()) };
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Pair"
+            }
+
+            if let Some(status) = pair_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
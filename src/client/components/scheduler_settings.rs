@@ -0,0 +1,419 @@
+use crate::server::{
+    db,
+    schema::{SchedulerConfig, SolverMode, Weekday},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+
+fn load_config() -> SchedulerConfig {
+    match db::establish_connection() {
+        Ok(conn) => match db::load_scheduler_config(&conn) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to load scheduler config: {}", e);
+                SchedulerConfig::default()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            SchedulerConfig::default()
+        }
+    }
+}
+
+#[component]
+pub fn SchedulerSettings() -> Element {
+    let mut config = use_signal(load_config);
+    let mut save_status = use_signal(|| None::<String>);
+
+    let handle_save = move |_| {
+        let config_data = config.read().clone();
+        match db::establish_connection() {
+            Ok(conn) => match db::save_scheduler_config(&conn, &config_data) {
+                Ok(_) => {
+                    info!("Scheduler config saved");
+                    save_status.set(Some("Scheduler settings saved successfully".to_string()));
+                }
+                Err(e) => {
+                    error!("Failed to save scheduler config: {}", e);
+                    save_status.set(Some(format!("Error saving scheduler settings: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                save_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Work Week" }
+            p { class: "settings-hint", "Days the office is open. Schedules, capacities and exports only cover these days." }
+            div { class: "checkbox-row",
+                for day in Weekday::all() {
+                    {
+                        let is_work_day = config.read().work_days.contains(&day);
+                        // The last open day cannot be unticked
+                        let is_last = is_work_day && config.read().work_days.len() == 1;
+                        let day_key = day.clone();
+                        rsx! {
+                            label { key: "{day}", class: "day-checkbox",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: is_work_day,
+                                    disabled: is_last,
+                                    onclick: move |_| {
+                                        let mut config = config.write();
+                                        if config.work_days.contains(&day_key) {
+                                            config.work_days.retain(|d| *d != day_key);
+                                        } else {
+                                            config.work_days.push(day_key.clone());
+                                        }
+                                    }
+                                }
+                                "{day}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            h3 { "Office Capacity" }
+            p { class: "settings-hint", "Maximum headcount per day. Leave a field empty for no limit." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "capacity-default", "Any day" }
+                    input {
+                        id: "capacity-default",
+                        r#type: "number",
+                        min: "0",
+                        value: config.read().default_capacity.map(|c| c.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| {
+                            config.write().default_capacity = evt.value().parse().ok();
+                        }
+                    }
+                }
+                for day in config.read().work_week() {
+                    {
+                        let day_key = day.clone();
+                        rsx! {
+                            div { class: "settings-field",
+                                label { r#for: "capacity-{day}", "{day}" }
+                                input {
+                                    id: "capacity-{day}",
+                                    r#type: "number",
+                                    min: "0",
+                                    placeholder: "default",
+                                    value: config.read().day_capacities.get(&day).map(|c| c.to_string()).unwrap_or_default(),
+                                    oninput: move |evt: FormEvent| {
+                                        match evt.value().parse::<usize>() {
+                                            Ok(capacity) => {
+                                                config.write().day_capacities.insert(day_key.clone(), capacity);
+                                            }
+                                            Err(_) => {
+                                                config.write().day_capacities.remove(&day_key);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            h3 { "Parking" }
+            p { class: "settings-hint", "Spaces shared out each office day among employees marked as needing parking. When there are not enough, whoever has been given the smallest share of their days over the months counted for fairness parks first." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "parking-spaces", "Spaces" }
+                    input {
+                        id: "parking-spaces",
+                        r#type: "number",
+                        min: "0",
+                        value: "{config.read().parking_spaces}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().parking_spaces = evt.value().parse().unwrap_or(0);
+                        }
+                    }
+                }
+            }
+
+            h3 { "Sex Balance" }
+            p { class: "settings-hint", "Keeps each day's male/female ratio close to the company-wide ratio. 0 turns it off." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "sex-balance-weight", "Weight" }
+                    input {
+                        id: "sex-balance-weight",
+                        r#type: "number",
+                        min: "0",
+                        step: "0.5",
+                        value: "{config.read().sex_balance_weight}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().sex_balance_weight = evt.value().parse().unwrap_or(0.0);
+                        }
+                    }
+                }
+            }
+
+            h3 { "Day Repetition" }
+            p { class: "settings-hint", "Discourages giving people the same days as in recent months. The most recent month counts fully; the oldest loses the decay share of its weight." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "repetition-weight", "Weight" }
+                    input {
+                        id: "repetition-weight",
+                        r#type: "number",
+                        min: "0",
+                        step: "0.5",
+                        value: "{config.read().repetition_weight}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().repetition_weight = evt.value().parse().unwrap_or(0.0);
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "lookback-months", "Months of history" }
+                    input {
+                        id: "lookback-months",
+                        r#type: "number",
+                        min: "0",
+                        max: "12",
+                        value: "{config.read().lookback_months}",
+                        oninput: move |evt: FormEvent| {
+                            if let Ok(months) = evt.value().parse::<usize>() {
+                                config.write().lookback_months = months.min(12);
+                            }
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "recency-decay", "Recency decay" }
+                    input {
+                        id: "recency-decay",
+                        r#type: "number",
+                        min: "0",
+                        max: "1",
+                        step: "0.05",
+                        value: "{config.read().recency_decay}",
+                        oninput: move |evt: FormEvent| {
+                            if let Ok(decay) = evt.value().parse::<f64>() {
+                                config.write().recency_decay = decay.clamp(0.0, 1.0);
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "checkbox-row",
+                label { class: "day-checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: config.read().weekly_rotation,
+                        onclick: move |_| {
+                            let current = config.read().weekly_rotation;
+                            config.write().weekly_rotation = !current;
+                        }
+                    }
+                    "Vary days week by week, counting earlier weeks of the month as history"
+                }
+                label { class: "day-checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: config.read().make_up_closed_days,
+                        onclick: move |_| {
+                            let current = config.read().make_up_closed_days;
+                            config.write().make_up_closed_days = !current;
+                        }
+                    }
+                    "Move days lost to a closure to another day of the same week"
+                }
+            }
+
+            h3 { "Long-term Fairness" }
+            p { class: "settings-hint", "Keeps a count of everyone's weekdays in saved schedules so unpopular days such as Mondays and Fridays rotate fairly over the year. Each office day on a weekday someone has had more than their share of costs the weight." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "fairness-weight", "Weight" }
+                    input {
+                        id: "fairness-weight",
+                        r#type: "number",
+                        min: "0",
+                        step: "0.5",
+                        value: "{config.read().fairness_weight}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().fairness_weight = evt.value().parse().unwrap_or(0.0);
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "fairness-months", "Months counted" }
+                    input {
+                        id: "fairness-months",
+                        r#type: "number",
+                        min: "1",
+                        max: "36",
+                        value: "{config.read().fairness_months}",
+                        oninput: move |evt: FormEvent| {
+                            if let Ok(months) = evt.value().parse::<usize>() {
+                                config.write().fairness_months = months.clamp(1, 36);
+                            }
+                        }
+                    }
+                }
+            }
+
+            h3 { "Day Preferences" }
+            p { class: "settings-hint", "Cost of each office day outside an employee's preferred days. Unavailable days are always excluded." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "preference-weight", "Weight" }
+                    input {
+                        id: "preference-weight",
+                        r#type: "number",
+                        min: "0",
+                        step: "0.5",
+                        value: "{config.read().preference_weight}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().preference_weight = evt.value().parse().unwrap_or(0.0);
+                        }
+                    }
+                }
+            }
+
+            h3 { "Team Co-location" }
+            p { class: "settings-hint", "Cost of each day a team falls short of its days-together target." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "colocation-weight", "Weight" }
+                    input {
+                        id: "colocation-weight",
+                        r#type: "number",
+                        min: "0",
+                        step: "0.5",
+                        value: "{config.read().colocation_weight}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().colocation_weight = evt.value().parse().unwrap_or(0.0);
+                        }
+                    }
+                }
+            }
+
+            h3 { "National Service (NSP) Policy" }
+            p { class: "settings-hint", "Applies to NSP employees only, on top of the limits above." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "nsp-required-days", "Required days" }
+                    input {
+                        id: "nsp-required-days",
+                        r#type: "number",
+                        min: "0",
+                        max: "{config.read().work_days.len()}",
+                        placeholder: "own",
+                        value: config.read().nsp_policy.required_days.map(|d| d.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| {
+                            config.write().nsp_policy.required_days = evt.value().parse().ok();
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "nsp-max-per-day", "Max NSPs per day" }
+                    input {
+                        id: "nsp-max-per-day",
+                        r#type: "number",
+                        min: "0",
+                        placeholder: "no limit",
+                        value: config.read().nsp_policy.max_per_day.map(|c| c.to_string()).unwrap_or_default(),
+                        oninput: move |evt: FormEvent| {
+                            config.write().nsp_policy.max_per_day = evt.value().parse().ok();
+                        }
+                    }
+                }
+            }
+            div { class: "checkbox-row",
+                label { class: "day-checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: config.read().nsp_policy.require_colleague_overlap,
+                        onclick: move |_| {
+                            let current = config.read().nsp_policy.require_colleague_overlap;
+                            config.write().nsp_policy.require_colleague_overlap = !current;
+                        }
+                    }
+                    "Each NSP shares at least one day with a non-NSP colleague in the same role"
+                }
+            }
+
+            h3 { "Solver" }
+            p { class: "settings-hint", "Greedy is fast; each run draws a seed that is saved with the schedule so it can be repeated. Refinement steps then try moving and swapping flexible employees to even it out (0 turns this off). Exact searches every assignment within the time budget and reports whether the result is proven optimal. The budget covers one whole generation, shared between the weeks of the month and, when comparing options, between the options." }
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "solver-mode", "Mode" }
+                    select {
+                        id: "solver-mode",
+                        onchange: move |evt: FormEvent| {
+                            config.write().solver_mode = match evt.value().as_str() {
+                                "exact" => SolverMode::Exact,
+                                _ => SolverMode::Greedy,
+                            };
+                        },
+                        option { value: "greedy", selected: config.read().solver_mode == SolverMode::Greedy, "{SolverMode::Greedy}" }
+                        option { value: "exact", selected: config.read().solver_mode == SolverMode::Exact, "{SolverMode::Exact}" }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "solver-time-budget", "Time budget (s)" }
+                    input {
+                        id: "solver-time-budget",
+                        r#type: "number",
+                        min: "1",
+                        value: "{config.read().solver_time_budget_secs}",
+                        disabled: config.read().solver_mode == SolverMode::Greedy,
+                        oninput: move |evt: FormEvent| {
+                            if let Ok(secs) = evt.value().parse::<u64>() {
+                                config.write().solver_time_budget_secs = secs.max(1);
+                            }
+                        }
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "local-search-iterations", "Refinement steps" }
+                    input {
+                        id: "local-search-iterations",
+                        r#type: "number",
+                        min: "0",
+                        step: "100",
+                        value: "{config.read().local_search_iterations}",
+                        oninput: move |evt: FormEvent| {
+                            config.write().local_search_iterations = evt.value().parse().unwrap_or(0);
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_save,
+                "Save Scheduler Settings"
+            }
+
+            if let Some(status) = save_status.read().as_ref() {
+                div {
+                    class: format!("settings-status {}",
+                        if status.contains("successfully") { "success" } else { "error" }
+                    ),
+                    "{status}"
+                }
+            }
+        }
+    }
+}
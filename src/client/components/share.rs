@@ -1,4 +1,7 @@
-use crate::server::schema::MonthlySchedule;
+use crate::server::{
+    schema::{Desk, Location, ParkingPlan, ScheduleWeek},
+    seating::assign_month_desks,
+};
 // use chrono::Month;
 use dioxus::{
     logger::tracing::{error, info},
@@ -14,25 +17,33 @@ use crate::server::export::{
 
 const SHARE_CSS: Asset = asset!("/assets/styles/share.css");
 
+// Exports every week of the month, not just the one on screen
 #[component]
-pub fn ShareButton(schedule: MonthlySchedule, year: i32, month: u32) -> Element {
-    // Clone data needed for the async task
-    let schedule_clone = schedule.clone(); // Clone schedule for the async block
-
+pub fn ShareButton(
+    weeks: Vec<ScheduleWeek>,
+    locations: Vec<Location>,
+    desks: Vec<Desk>,
+    parking: ParkingPlan,
+    year: i32,
+    month: u32,
+) -> Element {
     let handle_click = move |_| {
-        // Clone again for the spawned task if necessary, or use the outer clone
-        let schedule_for_task = schedule_clone.clone();
+        let weeks = weeks.clone();
+        let locations = locations.clone();
+        let desks = desks.clone();
+        let parking = parking.clone();
         spawn(async move {
-            info!("Generate & Save CSV button clicked.");
-            match generate_xlsx_data(&schedule_for_task, year, month) {
+            info!("Generate & Save XLSX button clicked.");
+            let seats = assign_month_desks(&weeks, &desks);
+            match generate_xlsx_data(&weeks, &locations, &seats, &parking, year, month) {
                 // Ok((filename, csv_data)) => match save_csv_with_dialog(filename, csv_data).await {
                 Ok((filename, xlsx_data)) => match save_xlsx_with_dialog(filename, xlsx_data).await
                 {
-                    Ok(_) => info!("CSV save process completed."),
-                    Err(e) => error!("Failed during CSV save dialog/write: {}", e),
+                    Ok(_) => info!("XLSX save process completed."),
+                    Err(e) => error!("Failed during XLSX save dialog/write: {}", e),
                 },
                 Err(e) => {
-                    error!("Failed to generate CSV data: {}", e);
+                    error!("Failed to generate XLSX data: {}", e);
                 }
             }
         });
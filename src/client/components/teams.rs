@@ -0,0 +1,175 @@
+use crate::server::{
+    db,
+    schema::{SchedulerConfig, Team, Weekday},
+};
+use dioxus::{
+    logger::tracing::{error, info},
+    prelude::*,
+};
+
+const SCHEDULER_SETTINGS_CSS: Asset = asset!("/assets/styles/scheduler_settings.css");
+const DELETE_ICON: Asset = asset!("/assets/icons/delete.svg");
+
+fn load_teams() -> Vec<Team> {
+    match db::establish_connection() {
+        Ok(conn) => match db::get_all_teams(&conn) {
+            Ok(teams) => teams,
+            Err(e) => {
+                error!("Failed to load teams: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn load_work_week() -> Vec<Weekday> {
+    match db::establish_connection() {
+        Ok(conn) => match db::load_scheduler_config(&conn) {
+            Ok(config) => config.work_week(),
+            Err(e) => {
+                error!("Failed to load scheduler config: {}", e);
+                SchedulerConfig::default().work_week()
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            SchedulerConfig::default().work_week()
+        }
+    }
+}
+
+fn empty_team() -> Team {
+    Team {
+        id: 0,
+        name: String::new(),
+        colocation_days: 2,
+    }
+}
+
+#[component]
+pub fn Teams() -> Element {
+    let mut teams = use_signal(load_teams);
+    let mut new_team = use_signal(empty_team);
+    // Teammates cannot share more days than the office is open
+    let work_days = use_signal(|| load_work_week().len());
+    let mut team_status = use_signal(|| None::<String>);
+
+    let handle_add = move |_| {
+        let mut team = new_team.read().clone();
+        team.name = team.name.trim().to_string();
+        team.colocation_days = team.colocation_days.clamp(1, work_days());
+        if team.name.is_empty() {
+            team_status.set(Some("Enter a team name".to_string()));
+            return;
+        }
+
+        match db::establish_connection() {
+            Ok(conn) => match db::insert_team(&conn, &team) {
+                Ok(saved) => {
+                    info!("Added team: {}", saved);
+                    teams.write().push(saved);
+                    teams.write().sort_by(|a, b| a.name.cmp(&b.name));
+                    new_team.set(empty_team());
+                    team_status.set(None);
+                }
+                Err(e) => {
+                    error!("Failed to save team: {}", e);
+                    team_status.set(Some(format!("Error saving team: {}", e)));
+                }
+            },
+            Err(e) => {
+                error!("Failed to connect to database: {}", e);
+                team_status.set(Some(format!("Database connection error: {}", e)));
+            }
+        }
+    };
+
+    let mut handle_delete = move |id: usize| match db::establish_connection() {
+        Ok(conn) => match db::delete_team(&conn, id) {
+            Ok(_) => teams.write().retain(|team| team.id != id),
+            Err(e) => {
+                error!("Failed to delete team: {}", e);
+                team_status.set(Some(format!("Error deleting team: {}", e)));
+            }
+        },
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            team_status.set(Some(format!("Database connection error: {}", e)));
+        }
+    };
+
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: SCHEDULER_SETTINGS_CSS,
+        }
+        div { class: "scheduler-settings",
+            h3 { "Teams" }
+            p { class: "settings-hint", "Teammates are scheduled to share office days. Assign people to teams from the Employees page." }
+
+            if teams.read().is_empty() {
+                p { class: "settings-hint", "No teams yet." }
+            } else {
+                ul { class: "rule-list",
+                    for team in teams.read().iter().cloned() {
+                        li { key: "{team.id}", class: "rule-item",
+                            span { "{team}" }
+                            button {
+                                class: "rule-delete",
+                                title: "Delete team",
+                                onclick: move |_| handle_delete(team.id),
+                                img {
+                                    src: DELETE_ICON,
+                                    width: "20",
+                                    height: "20",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "settings-grid",
+                div { class: "settings-field",
+                    label { r#for: "team-name", "Name" }
+                    input {
+                        id: "team-name",
+                        class: "team-name-input",
+                        r#type: "text",
+                        value: "{new_team.read().name}",
+                        oninput: move |evt: FormEvent| new_team.write().name = evt.value()
+                    }
+                }
+                div { class: "settings-field",
+                    label { r#for: "team-colocation", "Days together" }
+                    input {
+                        id: "team-colocation",
+                        r#type: "number",
+                        min: "1",
+                        max: "{work_days}",
+                        value: "{new_team.read().colocation_days}",
+                        oninput: move |evt: FormEvent| {
+                            if let Ok(days) = evt.value().parse::<usize>() {
+                                new_team.write().colocation_days = days.clamp(1, work_days());
+                            }
+                        }
+                    }
+                }
+            }
+
+            button {
+                class: "button primary",
+                onclick: handle_add,
+                "Add Team"
+            }
+
+            if let Some(status) = team_status.read().as_ref() {
+                div { class: "settings-status error", "{status}" }
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use crate::client::components::SearchBar;
+use crate::client::components::{LeaveCalendar, SearchBar};
 use crate::server::{
     db::{
-        delete_employee, establish_connection, get_all_employees, insert_employee, update_employee,
+        delete_employee, establish_connection, get_all_employees, get_all_locations, get_all_teams,
+        insert_employee, load_scheduler_config, update_employee,
     },
-    schema::{Employee, Role, Sex, Weekday},
+    schema::{DeskAttribute, Employee, Location, Role, SchedulerConfig, Sex, Team, Weekday},
 };
 
 use dioxus::prelude::*;
@@ -40,6 +41,49 @@ pub fn EmployeesPage() -> Element {
         }
     });
 
+    let teams: Signal<Vec<Team>> = use_signal(|| match establish_connection() {
+        Ok(conn) => match get_all_teams(&conn) {
+            Ok(teams) => teams,
+            Err(e) => {
+                eprintln!("Failed to load teams: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    });
+
+    let locations: Signal<Vec<Location>> = use_signal(|| match establish_connection() {
+        Ok(conn) => match get_all_locations(&conn) {
+            Ok(locations) => locations,
+            Err(e) => {
+                eprintln!("Failed to load locations: {}", e);
+                Vec::new()
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    });
+
+    // Day choices follow the configured work week
+    let work_week: Signal<Vec<Weekday>> = use_signal(|| match establish_connection() {
+        Ok(conn) => match load_scheduler_config(&conn) {
+            Ok(config) => config.work_week(),
+            Err(e) => {
+                eprintln!("Failed to load scheduler config: {}", e);
+                SchedulerConfig::default().work_week()
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            SchedulerConfig::default().work_week()
+        }
+    });
+
     let mut search_query = use_signal(String::new);
     let mut modal_state = use_signal(|| ModalType::None);
     let mut current_employee = use_signal(|| Employee {
@@ -50,6 +94,13 @@ pub fn EmployeesPage() -> Element {
         required_days: 2,
         fixed_days: Vec::new(),
         is_nsp: false,
+        preferred_days: Vec::new(),
+        unavailable_days: Vec::new(),
+        team_id: None,
+        location_id: None,
+        attends_location_id: None,
+        desk_needs: Vec::new(),
+        needs_parking: false,
     });
 
     let mut next_id = use_signal(|| employees.read().iter().map(|e| e.id).max().unwrap_or(0) + 1);
@@ -82,6 +133,13 @@ pub fn EmployeesPage() -> Element {
             required_days: 2,
             fixed_days: Vec::new(),
             is_nsp: false,
+            preferred_days: Vec::new(),
+            unavailable_days: Vec::new(),
+            team_id: None,
+            location_id: None,
+            attends_location_id: None,
+            desk_needs: Vec::new(),
+            needs_parking: false,
         });
         modal_state.set(ModalType::Add);
     };
@@ -203,6 +261,36 @@ pub fn EmployeesPage() -> Element {
         current_employee.write().fixed_days = days;
     };
 
+    let mut toggle_preferred_day = move |day: Weekday| {
+        let mut days = current_employee.read().preferred_days.clone();
+        if let Some(pos) = days.iter().position(|d| d == &day) {
+            days.remove(pos);
+        } else {
+            days.push(day);
+        }
+        current_employee.write().preferred_days = days;
+    };
+
+    let mut toggle_desk_need = move |need: DeskAttribute| {
+        let mut needs = current_employee.read().desk_needs.clone();
+        if let Some(pos) = needs.iter().position(|n| n == &need) {
+            needs.remove(pos);
+        } else {
+            needs.push(need);
+        }
+        current_employee.write().desk_needs = needs;
+    };
+
+    let mut toggle_unavailable_day = move |day: Weekday| {
+        let mut days = current_employee.read().unavailable_days.clone();
+        if let Some(pos) = days.iter().position(|d| d == &day) {
+            days.remove(pos);
+        } else {
+            days.push(day);
+        }
+        current_employee.write().unavailable_days = days;
+    };
+
     let toggle_nsp = move |_| {
         // Extract the current value first, then set the new value
         let current_value = current_employee.read().is_nsp;
@@ -393,16 +481,16 @@ pub fn EmployeesPage() -> Element {
                                 div { class: "form-group",
                                     label { "Required Days" }
                                     div { class: "radio-group",
-                                        for (id, val) in [("days-1", 1), ("days-2", 2), ("days-3", 3), ("days-5", 5)] {
+                                        for val in 0..=work_week.read().len() as u8 {
                                             div { class: "radio-option",
                                                 input {
                                                     r#type: "radio",
-                                                    id: "{id}",
+                                                    id: "days-{val}",
                                                     name: "required-days",
                                                     checked: current_employee.read().required_days == val,
                                                     onclick: move |_| update_required_days(val)
                                                 }
-                                                label { r#for: "{id}", "{val}" }
+                                                label { r#for: "days-{val}", "{val}" }
                                             }
                                         }
                                     }
@@ -410,13 +498,7 @@ pub fn EmployeesPage() -> Element {
                                 div { class: "form-group",
                                     label { "Fixed Days" }
                                     div { class: "checkbox-group",
-                                        for (id, day) in [
-                                            ("monday", Weekday::Monday),
-                                            ("tuesday", Weekday::Tuesday),
-                                            ("wednesday", Weekday::Wednesday),
-                                            ("thursday", Weekday::Thursday),
-                                            ("friday", Weekday::Friday)
-                                        ] {
+                                        for (id, day) in work_week.read().iter().map(|day| (day.to_string().to_lowercase(), day.clone())) {
                                             div { class: "checkbox-option",
                                                 input {
                                                     r#type: "checkbox",
@@ -429,6 +511,54 @@ pub fn EmployeesPage() -> Element {
                                         }
                                     }
                                 }
+                                div { class: "form-group",
+                                    label { "Preferred Days" }
+                                    div { class: "checkbox-group",
+                                        for (id, day) in work_week.read().iter().map(|day| (format!("preferred-{}", day.to_string().to_lowercase()), day.clone())) {
+                                            div { class: "checkbox-option",
+                                                input {
+                                                    r#type: "checkbox",
+                                                    id: "{id}",
+                                                    checked: current_employee.read().preferred_days.contains(&day),
+                                                    onclick: move |_| toggle_preferred_day(day.clone())
+                                                }
+                                                label { r#for: "{id}", "{day.to_string()}" }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "form-group",
+                                    label { "Unavailable Days" }
+                                    div { class: "checkbox-group",
+                                        for (id, day) in work_week.read().iter().map(|day| (format!("unavailable-{}", day.to_string().to_lowercase()), day.clone())) {
+                                            div { class: "checkbox-option",
+                                                input {
+                                                    r#type: "checkbox",
+                                                    id: "{id}",
+                                                    checked: current_employee.read().unavailable_days.contains(&day),
+                                                    onclick: move |_| toggle_unavailable_day(day.clone())
+                                                }
+                                                label { r#for: "{id}", "{day.to_string()}" }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "form-group",
+                                    label { "Desk Needs" }
+                                    div { class: "checkbox-group",
+                                        for (index, need) in DeskAttribute::values().iter().enumerate() {
+                                            div { class: "checkbox-option",
+                                                input {
+                                                    r#type: "checkbox",
+                                                    id: "desk-need-{index}",
+                                                    checked: current_employee.read().desk_needs.contains(need),
+                                                    onclick: move |_| toggle_desk_need(need.clone())
+                                                }
+                                                label { r#for: "desk-need-{index}", "{need}" }
+                                            }
+                                        }
+                                    }
+                                }
                                 // div { class: "form-group",
                                 //     div { class: "checkbox-option",
                                 //         input {
@@ -450,6 +580,62 @@ pub fn EmployeesPage() -> Element {
                                         option { value: "false", selected: !current_employee.read().is_nsp, "No" }
                                     }
                                 }
+                                div { class: "form-group",
+                                    label { r#for: "needs-parking", "Needs Parking ?" }
+                                    select {
+                                        id: "needs-parking",
+                                        class: "form-control nsp-select",
+                                        onchange: move |event| {
+                                            current_employee.write().needs_parking = event.value() == "true";
+                                        },
+                                        option { value: "true", selected: current_employee.read().needs_parking, "Yes" }
+                                        option { value: "false", selected: !current_employee.read().needs_parking, "No" }
+                                    }
+                                }
+                                div { class: "form-group",
+                                    label { r#for: "team", "Team" }
+                                    select {
+                                        id: "team",
+                                        class: "form-control role-select",
+                                        onchange: move |event| {
+                                            current_employee.write().team_id = event.value().parse::<usize>().ok();
+                                        },
+                                        option { value: "", selected: current_employee.read().team_id.is_none(), "No team" }
+                                        for team in teams.read().iter() {
+                                            option { value: "{team.id}", selected: current_employee.read().team_id == Some(team.id), "{team.name}" }
+                                        }
+                                    }
+                                }
+                                if !locations.read().is_empty() {
+                                    div { class: "form-group",
+                                        label { r#for: "location", "Home Location" }
+                                        select {
+                                            id: "location",
+                                            class: "form-control role-select",
+                                            onchange: move |event| {
+                                                current_employee.write().location_id = event.value().parse::<usize>().ok();
+                                            },
+                                            option { value: "", selected: current_employee.read().location_id.is_none(), "No location" }
+                                            for location in locations.read().iter() {
+                                                option { value: "{location.id}", selected: current_employee.read().location_id == Some(location.id), "{location.name}" }
+                                            }
+                                        }
+                                    }
+                                    div { class: "form-group",
+                                        label { r#for: "attends-location", "Attends" }
+                                        select {
+                                            id: "attends-location",
+                                            class: "form-control role-select",
+                                            onchange: move |event| {
+                                                current_employee.write().attends_location_id = event.value().parse::<usize>().ok();
+                                            },
+                                            option { value: "", selected: current_employee.read().attends_location_id.is_none(), "Home location" }
+                                            for location in locations.read().iter() {
+                                                option { value: "{location.id}", selected: current_employee.read().attends_location_id == Some(location.id), "{location.name}" }
+                                            }
+                                        }
+                                    }
+                                }
                             }
                             div { class: "modal-footer",
                                 button {
@@ -532,7 +718,13 @@ pub fn EmployeesPage() -> Element {
                                         ("Role:", current_employee.read().role.to_string()),
                                         ("Required Days:", current_employee.read().required_days.to_string()),
                                         ("Fixed Days:", current_employee.read().fixed_days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+                                        ("Preferred Days:", current_employee.read().preferred_days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+                                        ("Team:", current_employee.read().team_id.and_then(|id| teams.read().iter().find(|t| t.id == id).map(|t| t.name.clone())).unwrap_or_default()),
+                                        ("Location:", current_employee.read().office_location().and_then(|id| locations.read().iter().find(|l| l.id == id).map(|l| l.name.clone())).unwrap_or_default()),
+                                        ("Unavailable Days:", current_employee.read().unavailable_days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+                                        ("Desk Needs:", current_employee.read().desk_needs.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")),
                                         ("Is NSP:", (if current_employee.read().is_nsp { "Yes" } else { "No" }).to_string()),
+                                        ("Needs Parking:", (if current_employee.read().needs_parking { "Yes" } else { "No" }).to_string()),
                                     ] {
                                         div { class: "detail-row",
                                             span { class: "detail-label", "{label}" }
@@ -540,6 +732,7 @@ pub fn EmployeesPage() -> Element {
                                         }
                                     }
                                 }
+                                LeaveCalendar { key: "{current_employee.read().id}", employee_id: current_employee.read().id }
                             }
                             div { class: "modal-footer",
                                 button {
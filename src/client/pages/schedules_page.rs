@@ -1,8 +1,22 @@
-use crate::client::components::{SearchBar, ShareButton};
+use crate::client::components::{Meetings, SearchBar, ShareButton};
 use crate::server::{
-    db::{establish_connection, get_all_employees, load_schedule_from_db, save_schedule_to_db},
-    scheduler::generate_balanced_schedule,
-    schema::{Employee, MonthlySchedule, Weekday},
+    calendar::{
+        date_for, month_pattern, to_dated, weekday_of, weeks_from_dated, weeks_from_pattern,
+    },
+    db::{
+        establish_connection, get_all_closures, get_all_desks, get_all_employees,
+        get_all_locations, load_day_ledger, load_parking_ledger, load_schedule_constraints,
+        load_schedule_days, load_schedule_from_db, load_schedule_seed, load_scheduler_config,
+        save_parking_ledger, save_schedule_days, save_schedule_to_db,
+    },
+    feasibility::check_feasibility,
+    parking::allocate_parking,
+    scheduler::{generate_month_schedule, generate_schedule_options, sex_counts},
+    schema::{
+        Employee, FeasibilityIssue, ParkingLedger, ScheduleConstraints, ScheduleOption,
+        ScheduleWeek, SchedulerConfig, Weekday,
+    },
+    seating::assign_desks,
 };
 use chrono::{Datelike, Local, Month, NaiveDate};
 use dioxus::{
@@ -11,9 +25,12 @@ use dioxus::{
     prelude::*,
 };
 use std::collections::{HashMap, HashSet};
+use tokio::task::{spawn_blocking, JoinError};
 
 const SCHEDULES_CSS: Asset = asset!("/assets/styles/schedules.css");
 const EDIT_ICON: Asset = asset!("/assets/icons/edit.svg");
+const LOCK_ICON: Asset = asset!("/assets/icons/lock.svg");
+const UNLOCK_ICON: Asset = asset!("/assets/icons/unlock.svg");
 const X_CLOSE_ICON: Asset = asset!("/assets/icons/x-close.svg");
 const ARROW_RIGHT_ICON: Asset = asset!("/assets/icons/arrow-right.svg");
 const ARROW_LEFT_ICON: Asset = asset!("/assets/icons/arrow-left.svg");
@@ -27,6 +44,26 @@ enum ModalView {
     EditSchedule(Weekday, usize), // Original Day (can be ignored if needed), Employee ID
 }
 
+#[derive(PartialEq, Clone, Copy)]
+enum GenerationRun {
+    Single(Option<u64>), // Seed to reproduce, or None for a fresh one
+    Options(usize),      // Number of schedules to compare
+}
+
+// Schedules offered by "Generate Options"
+const OPTION_COUNT: usize = 5;
+
+// First and last of the `months` saved months before the given one, for the ledgers
+fn ledger_window(year: i32, month: u32, months: usize) -> ((i32, u32), (i32, u32)) {
+    let month_index = year * 12 + month as i32 - 1;
+    let from = month_index - months as i32;
+    let to = month_index - 1;
+    (
+        (from.div_euclid(12), from.rem_euclid(12) as u32 + 1),
+        (to.div_euclid(12), to.rem_euclid(12) as u32 + 1),
+    )
+}
+
 #[component]
 pub fn SchedulesPage() -> Element {
     // --- State Signals ---
@@ -47,11 +84,68 @@ pub fn SchedulesPage() -> Element {
         }
     });
 
-    let mut current_schedule: Signal<Option<MonthlySchedule>> = use_signal(|| None);
+    let scheduler_config = use_signal(|| match establish_connection() {
+        Ok(conn) => load_scheduler_config(&conn).unwrap_or_else(|e| {
+            error!("Failed to load scheduler config: {}", e);
+            SchedulerConfig::default()
+        }),
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            SchedulerConfig::default()
+        }
+    });
+    // Columns of the table, edit modal and option comparison
+    let work_week = use_memo(move || scheduler_config.read().work_week());
+
+    let locations = use_signal(|| match establish_connection() {
+        Ok(conn) => get_all_locations(&conn).unwrap_or_else(|e| {
+            error!("Failed to load locations: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    });
+    let desks = use_signal(|| match establish_connection() {
+        Ok(conn) => get_all_desks(&conn).unwrap_or_else(|e| {
+            error!("Failed to load desks: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    });
+    // Location the table is narrowed to (None = everyone)
+    let mut selected_location = use_signal(|| None::<usize>);
+
+    let closures = use_signal(|| match establish_connection() {
+        Ok(conn) => get_all_closures(&conn).unwrap_or_else(|e| {
+            error!("Failed to load closures: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            Vec::new()
+        }
+    });
+
+    // Calendar weeks of the selected month; empty until a schedule is loaded or generated
+    let mut current_weeks: Signal<Vec<ScheduleWeek>> = use_signal(Vec::new);
+    let mut selected_week = use_signal(|| 0usize);
     let mut edit_days: Signal<HashSet<Weekday>> = use_signal(HashSet::new);
+    // Employee/date pairs locked on the table, kept when regenerating
+    let mut pinned: Signal<HashSet<(usize, NaiveDate)>> = use_signal(HashSet::new);
+    // Parking given and asked for in the saved months before the selected one
+    let mut parking_history = use_signal(ParkingLedger::new);
     let mut search_query = use_signal(String::new);
     let mut is_generating = use_signal(|| false);
     let mut error_message = use_signal(|| None::<String>);
+    let mut feasibility_issues: Signal<Vec<FeasibilityIssue>> = use_signal(Vec::new);
+    let mut blocked_run = use_signal(|| None::<GenerationRun>);
+    let mut schedule_options: Signal<Vec<ScheduleOption>> = use_signal(Vec::new);
+    let mut schedule_seed = use_signal(|| None::<u64>);
     let mut modal_view = use_signal(|| ModalView::None);
     let mut selected_employee = use_signal(|| None::<usize>);
 
@@ -67,23 +161,57 @@ pub fn SchedulesPage() -> Element {
         let month = selected_month();
         info!("Loading schedule for {}-{}", month, year);
         error_message.set(None);
-        current_schedule.set(None);
+        feasibility_issues.set(Vec::new());
+        blocked_run.set(None);
+        schedule_options.set(Vec::new());
+        pinned.set(HashSet::new());
+        schedule_seed.set(None);
+        selected_week.set(0);
+        current_weeks.set(Vec::new());
 
         spawn(async move {
             match establish_connection() {
-                Ok(conn) => match load_schedule_from_db(&conn, year, month) {
-                    Ok(Some(schedule)) => {
-                        info!("Loaded existing schedule from DB for {}-{}", month, year);
-                        current_schedule.set(Some(schedule));
-                    }
-                    Ok(None) => {
-                        info!("No existing schedule found in DB for {}-{}", month, year);
+                Ok(conn) => {
+                    let (from, to) =
+                        ledger_window(year, month, scheduler_config.peek().fairness_months);
+                    match load_parking_ledger(&conn, from, to) {
+                        Ok(ledger) => parking_history.set(ledger),
+                        Err(e) => error!("Failed to load parking ledger: {}", e),
                     }
-                    Err(e) => {
-                        error!("Failed to load schedule for {}-{}: {}", month, year, e);
-                        error_message.set(Some(format!("Failed to load schedule: {}", e)));
+                    match load_schedule_from_db(&conn, year, month) {
+                        Ok(Some(pattern)) => {
+                            info!("Loaded existing schedule from DB for {}-{}", month, year);
+                            // Schedules saved before per-date storage repeat their pattern every week
+                            let work_days = scheduler_config.peek().work_days.clone();
+                            let weeks = match load_schedule_days(&conn, year, month) {
+                                Ok(Some(dated)) => weeks_from_dated(
+                                    year,
+                                    month,
+                                    &work_days,
+                                    &dated,
+                                    &employees.peek(),
+                                ),
+                                Ok(None) => weeks_from_pattern(year, month, &work_days, &pattern),
+                                Err(e) => {
+                                    error!("Failed to load schedule days: {}", e);
+                                    weeks_from_pattern(year, month, &work_days, &pattern)
+                                }
+                            };
+                            current_weeks.set(weeks);
+                            match load_schedule_seed(&conn, year, month) {
+                                Ok(seed) => schedule_seed.set(seed),
+                                Err(e) => error!("Failed to load schedule seed: {}", e),
+                            }
+                        }
+                        Ok(None) => {
+                            info!("No existing schedule found in DB for {}-{}", month, year);
+                        }
+                        Err(e) => {
+                            error!("Failed to load schedule for {}-{}: {}", month, year, e);
+                            error_message.set(Some(format!("Failed to load schedule: {}", e)));
+                        }
                     }
-                },
+                }
                 Err(e) => {
                     error!("Failed to connect to database for loading schedule: {}", e);
                     error_message.set(Some("Database connection error while loading.".to_string()));
@@ -106,10 +234,74 @@ pub fn SchedulesPage() -> Element {
         }
     });
 
+    // The selected week's schedule and what the scheduler reported for it
+    let current_schedule = use_memo(move || {
+        current_weeks
+            .read()
+            .get(selected_week())
+            .map(|week| week.result.schedule.clone())
+    });
+    // Desks follow the selected week, so they update with every edit
+    let seat_plan = use_memo(move || {
+        let schedule = current_schedule().unwrap_or_default();
+        assign_desks(&schedule, &work_week.read(), &desks.read())
+    });
+    // What the table shows: the selected week, narrowed to the selected location
+    let displayed_schedule = use_memo(move || {
+        let mut schedule = current_schedule()?;
+        if let Some(location_id) = selected_location() {
+            for day_employees in schedule.values_mut() {
+                day_employees.retain(|e| e.office_location() == Some(location_id));
+            }
+        }
+        Some(schedule)
+    });
+    let current_dates = use_memo(move || {
+        current_weeks
+            .read()
+            .get(selected_week())
+            .map(|week| week.dates.clone())
+            .unwrap_or_default()
+    });
+    let dated_schedule = use_memo(move || to_dated(&current_weeks.read()));
+    let parking_plan = use_memo(move || {
+        allocate_parking(
+            &current_weeks.read(),
+            scheduler_config.read().parking_spaces,
+            &parking_history.read(),
+        )
+    });
+    let schedule_warnings = use_memo(move || {
+        current_weeks
+            .read()
+            .get(selected_week())
+            .map(|week| week.result.warnings.clone())
+            .unwrap_or_default()
+    });
+    // Only available for a schedule generated in this session
+    let schedule_explanations = use_memo(move || {
+        current_weeks
+            .read()
+            .get(selected_week())
+            .map(|week| week.result.explanations.clone())
+            .unwrap_or_default()
+    });
+    let solver_status = use_memo(move || {
+        let optimal = current_weeks
+            .read()
+            .get(selected_week())
+            .and_then(|week| week.result.proven_optimal)?;
+        Some(if optimal {
+            "Exact solver: schedule is proven optimal.".to_string()
+        } else {
+            "Exact solver: time budget reached, showing the best schedule found.".to_string()
+        })
+    });
+
     let day_counts = use_memo(move || {
         let mut counts: HashMap<Weekday, usize> = HashMap::new();
-        if let Some(schedule) = &*current_schedule.read() {
-            for day in Weekday::values() {
+        if let Some(schedule) = &*displayed_schedule.read() {
+            for day in work_week.read().iter() {
                 counts.insert(day.clone(), schedule.get(day).map_or(0, |v| v.len()));
             }
         }
@@ -170,7 +362,16 @@ pub fn SchedulesPage() -> Element {
         }
     };
 
-    let handle_generate = move |_| {
+    let mut apply_weeks = move |weeks: Vec<ScheduleWeek>| {
+        schedule_seed.set(weeks.first().map(|week| week.result.seed));
+        if selected_week() >= weeks.len() {
+            selected_week.set(0);
+        }
+        current_weeks.set(weeks);
+    };
+
+    // Unless skipped, problems found by the feasibility check are shown instead of a schedule
+    let mut run_generation = move |run: GenerationRun, skip_checks: bool| {
         if *is_generating.read() {
             return;
         }
@@ -179,6 +380,11 @@ pub fn SchedulesPage() -> Element {
         let year = selected_year();
         let month = selected_month();
         let current_employees = employees.read().clone();
+        let config = scheduler_config.read().clone();
+        let mut date_pins: HashMap<usize, Vec<NaiveDate>> = HashMap::new();
+        for (emp_id, date) in pinned.read().iter() {
+            date_pins.entry(*emp_id).or_default().push(*date);
+        }
 
         spawn(async move {
             let today = Local::now().date_naive();
@@ -201,26 +407,136 @@ pub fn SchedulesPage() -> Element {
 
             // let past_schedules = HashMap::new(); // Placeholder
             // Get past schedules
-            let past_schedules = get_past_schedules(year, month, &current_employees).await;
-            info!("Generating schedule for {}-{}", month, year);
-            let schedule = generate_balanced_schedule(&current_employees, &past_schedules);
-            current_schedule.set(Some(schedule));
+            let past_schedules =
+                get_past_schedules(year, month, &current_employees, config.lookback_months).await;
+            let constraints = match establish_connection() {
+                Ok(conn) => {
+                    let mut constraints = load_schedule_constraints(&conn).unwrap_or_else(|e| {
+                        error!("Failed to load schedule constraints: {}", e);
+                        ScheduleConstraints::default()
+                    });
+                    // Saved months before this one, as far back as the fairness window reaches
+                    let (from, to) = ledger_window(year, month, config.fairness_months);
+                    constraints.ledger = load_day_ledger(&conn, from, to).unwrap_or_else(|e| {
+                        error!("Failed to load day ledger: {}", e);
+                        Default::default()
+                    });
+                    constraints
+                }
+                Err(e) => {
+                    error!("Failed to connect to database: {}", e);
+                    ScheduleConstraints::default()
+                }
+            };
+            if !skip_checks {
+                let issues =
+                    check_feasibility(&current_employees, &config, &constraints, &date_pins);
+                if !issues.is_empty() {
+                    info!("Generation stopped: {} feasibility issue(s)", issues.len());
+                    feasibility_issues.set(issues);
+                    blocked_run.set(Some(run));
+                    is_generating.set(false);
+                    return;
+                }
+            }
+            feasibility_issues.set(Vec::new());
+            blocked_run.set(None);
+            // The exact solver can take its whole time budget, so generation runs on a
+            // blocking thread and the page stays responsive meanwhile
+            let mut report_failure = move |e: JoinError| {
+                error!("Schedule generation failed: {}", e);
+                error_message.set(Some(format!("Schedule generation failed: {}", e)));
+            };
+            match run {
+                GenerationRun::Single(seed) => {
+                    info!("Generating schedule for {}-{}", month, year);
+                    let generated = spawn_blocking(move || {
+                        generate_month_schedule(
+                            &current_employees,
+                            &past_schedules,
+                            &config,
+                            &constraints,
+                            year,
+                            month,
+                            &date_pins,
+                            seed,
+                        )
+                    })
+                    .await;
+                    match generated {
+                        Ok(weeks) => {
+                            schedule_options.set(Vec::new());
+                            apply_weeks(weeks);
+                        }
+                        Err(e) => report_failure(e),
+                    }
+                }
+                GenerationRun::Options(count) => {
+                    info!(
+                        "Generating {} schedule options for {}-{}",
+                        count, month, year
+                    );
+                    let generated = spawn_blocking(move || {
+                        generate_schedule_options(
+                            &current_employees,
+                            &past_schedules,
+                            &config,
+                            &constraints,
+                            year,
+                            month,
+                            &date_pins,
+                            count,
+                        )
+                    })
+                    .await;
+                    match generated {
+                        Ok(options) => schedule_options.set(options),
+                        Err(e) => report_failure(e),
+                    }
+                }
+            }
             is_generating.set(false);
         });
     };
 
+    let handle_generate = move |_| run_generation(GenerationRun::Single(None), false);
+
+    let handle_generate_options =
+        move |_| run_generation(GenerationRun::Options(OPTION_COUNT), false);
+
+    let handle_generate_anyway = move |_| {
+        if let Some(run) = blocked_run() {
+            run_generation(run, true);
+        }
+    };
+
+    let handle_regenerate_same_seed = move |_| {
+        if let Some(seed) = schedule_seed() {
+            run_generation(GenerationRun::Single(Some(seed)), false);
+        }
+    };
+
+    let mut handle_pick_option = move |index: usize| {
+        let picked = schedule_options.read().get(index).cloned();
+        if let Some(option) = picked {
+            schedule_options.set(Vec::new());
+            apply_weeks(option.weeks);
+        }
+    };
+
     // implementation for get_past_schedules for each employee
     async fn get_past_schedules(
         year: i32,
         month: u32,
         employees: &[Employee],
+        lookback_months: usize,
     ) -> HashMap<usize, Vec<HashSet<Weekday>>> {
         let mut past_schedules: HashMap<usize, Vec<HashSet<Weekday>>> = HashMap::new();
         if let Ok(conn) = establish_connection() {
             for employee in employees {
                 past_schedules.insert(employee.id, Vec::new());
-                // Load schedules from the last 3 months
-                for i in 1..=3 {
+                // Load as many past months as the scorer looks back
+                for i in 1..=lookback_months as i32 {
                     let past_month = month as i32 - i;
                     let past_year = year - (if past_month < 1 { 1 } else { 0 });
                     let adjusted_month = if past_month < 1 {
@@ -250,13 +566,21 @@ pub fn SchedulesPage() -> Element {
     }
 
     let handle_save = move |_| {
-        if let Some(schedule_data) = current_schedule.read().clone() {
+        let weeks = current_weeks.read().clone();
+        if !weeks.is_empty() {
             let year = selected_year();
             let month = selected_month();
+            let seed = schedule_seed();
+            let pattern = month_pattern(&weeks);
+            let dated = to_dated(&weeks);
+            let parking = parking_plan();
             error_message.set(None);
             spawn(async move {
                 match establish_connection() {
-                    Ok(conn) => match save_schedule_to_db(&conn, year, month, &schedule_data) {
+                    Ok(conn) => match save_schedule_to_db(&conn, year, month, &pattern, seed)
+                        .and_then(|_| save_schedule_days(&conn, year, month, &dated))
+                        .and_then(|_| save_parking_ledger(&conn, year, month, &parking))
+                    {
                         Ok(_) => {
                             error_message.set(Some("Schedule saved successfully!".to_string()))
                         }
@@ -291,8 +615,14 @@ pub fn SchedulesPage() -> Element {
                 info!("Fetching past schedules for employee ID: {}", emp_id); // LOG 1
                                                                               // Pass only the relevant employee to get_past_schedules if possible,
                                                                               // or filter inside get_past_schedules. For now, passing all.
-                let employee_past_schedules_map =
-                    get_past_schedules(current_year, current_month, &current_employees).await;
+                let lookback_months = scheduler_config.read().lookback_months;
+                let employee_past_schedules_map = get_past_schedules(
+                    current_year,
+                    current_month,
+                    &current_employees,
+                    lookback_months,
+                )
+                .await;
                 info!(
                     "Fetched past schedules data: {:?}",
                     employee_past_schedules_map.get(&emp_id)
@@ -333,15 +663,28 @@ pub fn SchedulesPage() -> Element {
         modal_view.set(ModalView::EditSchedule(day.clone(), emp_id));
     };
 
+    // Edits apply to the selected week only
     let mut handle_update_schedule = move |emp_id: usize, new_days_set: HashSet<Weekday>| {
-        current_schedule.with_mut(|maybe_schedule| {
-            if let Some(schedule) = maybe_schedule {
+        let week_index = selected_week();
+        let week_dates = current_dates();
+        current_weeks.with_mut(|weeks| {
+            if let Some(week) = weeks.get_mut(week_index) {
+                let schedule = &mut week.result.schedule;
                 if let Some(emp) = employees.read().iter().find(|e| e.id == emp_id).cloned() {
+                    // Locks on days the employee no longer works are dropped
+                    pinned.write().retain(|(id, date)| {
+                        *id != emp_id
+                            || !week_dates.contains(date)
+                            || new_days_set.contains(&weekday_of(*date))
+                    });
                     for day_employees in schedule.values_mut() {
                         day_employees.retain(|e| e.id != emp_id);
                     }
+                    // Days outside the month have no entry and cannot be picked
                     for new_day in new_days_set {
-                        schedule.entry(new_day).or_default().push(emp.clone());
+                        if let Some(day_employees) = schedule.get_mut(&new_day) {
+                            day_employees.push(emp.clone());
+                        }
                     }
                     for day_employees in schedule.values_mut() {
                         day_employees.sort_by_key(|e| e.name.clone());
@@ -381,24 +724,157 @@ pub fn SchedulesPage() -> Element {
         }
     };
 
+    // --- Option Comparison ---
+    let options_element = {
+        let options = schedule_options.read().clone();
+        rsx! {
+            if !options.is_empty() {
+                div { class: "schedule-options",
+                    div { class: "schedule-options-header",
+                        h3 { "Compare Options" }
+                        button { class: "btn btn-secondary", onclick: move |_| schedule_options.set(Vec::new()), "Dismiss" }
+                    }
+                    table { class: "explanation-table options-table",
+                        thead { tr {
+                            th { "" }
+                            for index in 0..options.len() { th { key: "{index}", {format!("Option {}", index + 1)} } }
+                        } }
+                        tbody {
+                            for day in work_week.read().iter() {
+                                tr { key: "{day}",
+                                    td { "{day}" }
+                                    for (index, option) in options.iter().enumerate() {
+                                        td { key: "{index}", { option.day_counts.iter().find(|(d, _)| d == day).map_or(0, |(_, count)| *count).to_string() } }
+                                    }
+                                }
+                            }
+                            tr {
+                                td { "Variance" }
+                                for (index, option) in options.iter().enumerate() { td { key: "{index}", "{option.variance:.2}" } }
+                            }
+                            tr {
+                                td { "Repetition" }
+                                for (index, option) in options.iter().enumerate() { td { key: "{index}", "{option.repetition:.2}" } }
+                            }
+                            tr {
+                                td { "Rules broken" }
+                                for (index, option) in options.iter().enumerate() { td { key: "{index}", "{option.breaches}" } }
+                            }
+                            tr {
+                                td { "People unplaced" }
+                                for (index, option) in options.iter().enumerate() { td { key: "{index}", "{option.unplaced}" } }
+                            }
+                            tr {
+                                td { "" }
+                                for index in 0..options.len() {
+                                    td { key: "{index}",
+                                        button { class: "btn btn-primary", onclick: move |_| handle_pick_option(index), "Use" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    p { class: "explanation-hint", "Day rows count office days on that weekday over the whole month. Rules broken counts each coverage, NSP, pair, team or capacity rule breach once, even when it repeats every week." }
+                    if options.len() < OPTION_COUNT {
+                        p { class: "explanation-hint", "Only {options.len()} different schedules were found for these inputs." }
+                    }
+                }
+            }
+        }
+    };
+
+    // --- Week Tabs ---
+    let week_tabs_element = {
+        let week_labels: Vec<String> = current_weeks
+            .read()
+            .iter()
+            .map(|week| match (week.dates.first(), week.dates.last()) {
+                (Some(first), Some(last)) if first != last => {
+                    format!("{} - {}", first.format("%-d %b"), last.format("%-d %b"))
+                }
+                (Some(first), _) => first.format("%-d %b").to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        rsx! {
+            if week_labels.len() > 1 {
+                div { class: "week-tabs",
+                    for (index, label) in week_labels.into_iter().enumerate() {
+                        button {
+                            key: "{index}",
+                            class: if index == selected_week() { "week-tab active" } else { "week-tab" },
+                            onclick: move |_| selected_week.set(index),
+                            "{label}"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    // --- Location Tabs ---
+    let location_tabs_element = rsx! {
+        if !locations.read().is_empty() {
+            div { class: "week-tabs location-tabs",
+                button {
+                    class: if selected_location().is_none() { "week-tab active" } else { "week-tab" },
+                    onclick: move |_| selected_location.set(None),
+                    "All locations"
+                }
+                for location in locations.read().iter().cloned() {
+                    button {
+                        key: "{location.id}",
+                        class: if selected_location() == Some(location.id) { "week-tab active" } else { "week-tab" },
+                        onclick: move |_| selected_location.set(Some(location.id)),
+                        "{location.name}"
+                    }
+                }
+            }
+        }
+    };
+
     // --- Schedule Table Calculation ---
     let schedule_display_element = {
-        let schedule_read = current_schedule.read();
+        let schedule_read = displayed_schedule.read();
         match schedule_read.as_ref() {
             Some(schedule) if !schedule.is_empty() => {
                 let max_rows = schedule.values().map(|emps| emps.len()).max().unwrap_or(0);
                 let schedule_clone = schedule.clone();
+                let week_dates = current_dates();
 
                 rsx! { // Start of the *outer* rsx! for the table element
                     div { class: "schedule-table-container",
                         table { class: "schedule-table",
-                            thead { tr { for day in Weekday::values() { th { "{day}" span { class: "day-count", " ({day_counts().get(day).unwrap_or(&0)})" } } } } }
+                            thead { tr { for day in work_week.read().iter() {
+                                {
+                                    let count = *day_counts().get(day).unwrap_or(&0);
+                                    // A location's own desks when the table is narrowed to it
+                                    let capacity = match selected_location() {
+                                        Some(location_id) => locations.read().iter().find(|l| l.id == location_id).and_then(|l| l.capacity),
+                                        None => scheduler_config.read().capacity_for(day),
+                                    };
+                                    let over_capacity = capacity.is_some_and(|cap| count > cap);
+                                    let count_label = match capacity {
+                                        Some(cap) => format!(" ({}/{})", count, cap),
+                                        None => format!(" ({})", count),
+                                    };
+                                    let (males, females) = sex_counts(&schedule_clone, day);
+                                    let date = date_for(&week_dates, day);
+                                    let closure = date.and_then(|date| closures.read().iter().find(|c| c.date == date).cloned());
+                                    // Weekdays of a partial week that fall in the next or previous month
+                                    match date {
+                                        Some(date) if closure.is_some() => rsx! { th { class: "closed-day", "{day}" span { class: "day-date", {date.format("%-d %b").to_string()} } span { class: "closure-name", {closure.map(|c| c.name).unwrap_or_default()} } } },
+                                        Some(date) => rsx! { th { class: if over_capacity { "over-capacity" } else { "" }, "{day}" span { class: "day-date", {date.format("%-d %b").to_string()} } span { class: "day-count", "{count_label}" } span { class: "day-sex-split", "{males}M / {females}F" } } },
+                                        None => rsx! { th { class: "outside-month", "{day}" span { class: "day-date", "Other month" } } },
+                                    }
+                                }
+                            } } }
                             tbody {
-                                if max_rows == 0 { tr { td { colspan: Weekday::values().len() as u32, class: "empty-schedule-message", "Schedule is empty." } } }
+                                if max_rows == 0 { tr { td { colspan: work_week.read().len() as u32, class: "empty-schedule-message", "Schedule is empty." } } }
                                 else {
                                     for row_index in 0..max_rows {
                                         tr {
-                                            for day_ref in Weekday::values() {
+                                            for day_ref in work_week.read().iter() {
                                                 td {
                                                     if let Some(emp) = schedule_clone.get(day_ref).and_then(|emps| emps.get(row_index)) {
                                                         // CORRECT FIX: Use a standard Rust block { } here to contain the 'let' bindings
@@ -406,15 +882,53 @@ pub fn SchedulesPage() -> Element {
                                                             // These 'let' bindings are now *outside* any rsx! macro invocation
                                                             let emp_clone = emp.clone();
                                                             let day_clone = day_ref.clone();
+                                                            let pin_key = date_for(&week_dates, day_ref).map(|date| (emp.id, date));
+                                                            let is_pinned = pin_key.as_ref().is_some_and(|key| pinned.read().contains(key));
+                                                            let desk = seat_plan.read().get(day_ref).and_then(|seats| seats.get(&emp.id)).cloned();
+                                                            let no_desk = desk.is_none() && !desks.read().is_empty();
+                                                            // None for people who do not drive in
+                                                            let parked = emp.needs_parking.then(|| {
+                                                                pin_key.as_ref().is_some_and(|(_, date)| {
+                                                                    parking_plan.read().get(date).is_some_and(|day| day.parked.iter().any(|e| e.id == emp.id))
+                                                                })
+                                                            });
 
                                                             // Now, call rsx! *inside* this standard block to render the element
                                                             rsx! {
                                                                 div {
                                                                     key: "{day_ref}-{emp_clone.id}-{row_index}",
-                                                                    class: "schedule-employee-card",
+                                                                    class: if is_pinned { "schedule-employee-card pinned" } else { "schedule-employee-card" },
                                                                     onclick: move |_| handle_employee_click(emp_clone.id),
                                                                     div { class: "card-name", "{emp_clone.name}" }
                                                                     div { class: "card-role", "{emp_clone.role}" } // Assuming role implements Display
+                                                                    if let Some(desk) = desk {
+                                                                        div { class: "card-desk", title: "{desk}", "Desk {desk.name} · F{desk.floor} {desk.zone}" }
+                                                                    } else if no_desk {
+                                                                        div { class: "card-desk no-desk", "No free desk" }
+                                                                    }
+                                                                    match parked {
+                                                                        Some(true) => rsx! { div { class: "card-parking", "Parking" } },
+                                                                        Some(false) => rsx! { div { class: "card-parking no-parking", "No parking space" } },
+                                                                        None => rsx! {},
+                                                                    }
+                                                                    button {
+                                                                        class: "pin-schedule-entry",
+                                                                        title: if is_pinned { "Unlock: may move when regenerating" } else { "Lock: keep on this day when regenerating" },
+                                                                        onclick: move |evt| {
+                                                                            evt.stop_propagation();
+                                                                            if let Some(key) = pin_key {
+                                                                                let mut pins = pinned.write();
+                                                                                if !pins.remove(&key) {
+                                                                                    pins.insert(key);
+                                                                                }
+                                                                            }
+                                                                        },
+                                                                        img {
+                                                                            src: if is_pinned { LOCK_ICON } else { UNLOCK_ICON },
+                                                                            width: "18",
+                                                                            height: "18",
+                                                                        }
+                                                                    }
                                                                     button {
                                                                         class: "edit-schedule-entry", title: "Edit schedule",
                                                                         onclick: move |evt| {
@@ -499,18 +1013,51 @@ pub fn SchedulesPage() -> Element {
                 }
                 div { class: "action-buttons",
                     button { class: "btn btn-primary", onclick: handle_generate, disabled: *is_generating.read() || employees().is_empty(), title: if employees().is_empty() { "Add employees first" } else { "" }, "{generate_button_text()}" }
+                    button { class: "btn btn-secondary", onclick: handle_generate_options, disabled: *is_generating.read() || employees().is_empty(), title: "Generate {OPTION_COUNT} schedules to compare", "Generate {OPTION_COUNT} Options" }
+                    if let Some(seed) = schedule_seed() {
+                        button { class: "btn btn-secondary", onclick: handle_regenerate_same_seed, disabled: *is_generating.read() || employees().is_empty(), title: "Regenerate with seed {seed}", "Same Seed" }
+                    }
                     button { class: "btn btn-secondary", onclick: handle_save, disabled: current_schedule.read().is_none(), "Save" }
-                    if let Some(schedule_data) = current_schedule.read().clone() {
-                        if !schedule_data.is_empty() { ShareButton { schedule: schedule_data, year: selected_year(), month: selected_month() } }
+                    if current_schedule.read().as_ref().is_some_and(|schedule| !schedule.is_empty()) {
+                        ShareButton { weeks: current_weeks(), locations: locations(), desks: desks(), parking: parking_plan(), year: selected_year(), month: selected_month() }
                     }
                 }
             }
 
             // --- Error Message Area ---
             if let Some(msg) = &*error_message.read() { div { class: "error-message", "{msg}" } }
+            if let Some(status) = &*solver_status.read() { div { class: "solver-status", "{status}" } }
+            if !feasibility_issues.read().is_empty() {
+                div { class: "error-message schedule-warnings feasibility-issues",
+                    p { "The schedule was not generated. Fix these problems first:" }
+                    ul {
+                        for (index, issue) in feasibility_issues.read().iter().enumerate() {
+                            li { key: "{index}", "{issue}" }
+                        }
+                    }
+                    button { class: "btn btn-secondary", onclick: handle_generate_anyway, disabled: *is_generating.read(), "Generate Anyway" }
+                }
+            }
+            if !schedule_warnings.read().is_empty() {
+                div { class: "error-message schedule-warnings",
+                    ul {
+                        for (index, warning) in schedule_warnings.read().iter().enumerate() {
+                            li { key: "{index}", "{warning}" }
+                        }
+                    }
+                }
+            }
+
+            {options_element}
 
             // --- Schedule Display Area ---
+            {week_tabs_element}
+            {location_tabs_element}
             {schedule_display_element} // Render the pre-computed element
+            if !current_weeks.read().is_empty() {
+                // Keyed by month so the bookings reload when the month changes
+                Meetings { key: "{selected_year()}-{selected_month()}", dated: dated_schedule(), employees: employees(), year: selected_year(), month: selected_month() }
+            }
 
             // --- Modals ---
             if *modal_view.read() != ModalView::None {
@@ -588,6 +1135,36 @@ pub fn SchedulesPage() -> Element {
                                                     p { class: "past-schedule-message", "Loading past schedules..."}
                                                 }
                                         }
+                                        div { class: "past-schedules schedule-explanation", h4 { "How These Days Were Chosen" },
+                                            if let Some(explanation) = schedule_explanations.read().get(&emp_id) {
+                                                p { class: "explanation-placement", strong { "Placement: " } "{explanation.placement}" }
+                                                if explanation.considered.is_empty() {
+                                                    p { class: "past-schedule-message", "No combinations were scored for this employee." }
+                                                } else {
+                                                    table { class: "explanation-table",
+                                                        thead { tr { th { "Days" } th { "Variance" } th { "Repetition" } th { "Fairness" } th { "Coverage" } th { "Sex balance" } th { "Preference" } th { "Team" } th { "Total" } } }
+                                                        tbody {
+                                                            for (index, score) in explanation.considered.iter().enumerate() {
+                                                                tr { key: "{index}", class: if score.chosen { "chosen" } else { "" },
+                                                                    td { { score.days.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(", ") } }
+                                                                    td { "{score.variance:.2}" }
+                                                                    td { "{score.repetition:.2}" }
+                                                                    td { "{score.fairness:.2}" }
+                                                                    td { "{score.coverage:.2}" }
+                                                                    td { "{score.sex_balance:.2}" }
+                                                                    td { "{score.preference:.2}" }
+                                                                    td { "{score.colocation:.2}" }
+                                                                    td { "{score.total:.2}" }
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                    p { class: "explanation-hint", "Lower totals are better. The highlighted row is the combination in the schedule." }
+                                                }
+                                            } else {
+                                                p { class: "past-schedule-message", "Generate the schedule to see how these days were chosen." }
+                                            }
+                                        }
                                     }
                                 } else { div { class: "employee-details-modal", h3 { "Error" }, p { "Employee details not found."} } }
                             },
@@ -596,7 +1173,9 @@ pub fn SchedulesPage() -> Element {
                                     div { class: "edit-schedule-modal",
                                         h3 { "Edit Schedule for {emp.name}" }, p { "Select work days for {month_name()} {selected_year()}:" },
                                         div { class: "day-selection",
-                                            for weekday_ref in Weekday::values() { { // Scope for checkbox logic
+                                            for weekday_ref in work_week.read().iter().filter(|day| {
+                                                date_for(&current_dates(), day).is_some_and(|date| !closures.read().iter().any(|c| c.date == date))
+                                            }) { { // Scope for checkbox logic
                                                 let current_edit_days = edit_days.read(); let is_checked = current_edit_days.contains(weekday_ref);
                                                 let weekday_clone = weekday_ref.clone();
                                                 rsx!( label { class: "day-checkbox", input { r#type: "checkbox", checked: is_checked, oninput: move |evt: Event<FormData>| { let checked: bool = evt.value().parse().unwrap_or(false); edit_days.with_mut(|days| { if checked { days.insert(weekday_clone.clone()); } else { days.remove(&weekday_clone); } }); } }, span { class: if is_checked { "day-selected" } else { "" }, "{weekday_ref}" } } )
@@ -623,19 +1202,3 @@ pub fn SchedulesPage() -> Element {
         }
     }
 }
-
-// Helper Trait/Impl for Weekday iteration
-mod weekday_helper {
-    use super::Weekday;
-    impl Weekday {
-        pub fn values() -> &'static [Weekday] {
-            &[
-                Weekday::Monday,
-                Weekday::Tuesday,
-                Weekday::Wednesday,
-                Weekday::Thursday,
-                Weekday::Friday,
-            ]
-        }
-    }
-}
@@ -1,4 +1,7 @@
-use crate::client::components::ImportButton;
+use crate::client::components::{
+    Closures, CoverageRules, Desks, ImportButton, Locations, MeetingRooms, PairConstraints,
+    SchedulerSettings, Teams,
+};
 use crate::server::db;
 use dioxus::{
     logger::tracing::{error, info},
@@ -183,6 +186,18 @@ pub fn SettingsPage() -> Element {
                     }
                 }
             }
+
+            div { class: "settings-section scheduler-section",
+                h2 { "Scheduler" }
+                SchedulerSettings {}
+                CoverageRules {}
+                Locations {}
+                Desks {}
+                MeetingRooms {}
+                Teams {}
+                PairConstraints {}
+                Closures {}
+            }
         }
 
         // Confirmation Modals (Conditionally rendered)
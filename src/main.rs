@@ -15,7 +15,13 @@ use dioxus::{logger::tracing::Level, prelude::*};
 use dioxus_desktop::{tao::window::Fullscreen, Config, WindowBuilder};
 
 use crate::client::app::App;
-use crate::server::db::{create_employee_table, create_schedules_table, establish_connection};
+use crate::server::db::{
+    create_bookings_table, create_closures_table, create_coverage_rules_table,
+    create_day_ledger_table, create_desks_table, create_employee_table, create_leave_table,
+    create_locations_table, create_meeting_rooms_table, create_pair_constraints_table,
+    create_parking_ledger_table, create_schedule_days_table, create_scheduler_config_table,
+    create_schedules_table, create_teams_table, establish_connection,
+};
 
 fn main() {
     dioxus::logger::init(Level::INFO).expect("failed to init logger");
@@ -42,6 +48,45 @@ fn main() {
                 eprintln!("Failed to create schedules table: {}", e);
                 // Handle the error appropriately (e.g., exit the application)
             }
+            if let Err(e) = create_schedule_days_table(&conn) {
+                eprintln!("Failed to create schedule days table: {}", e);
+            }
+            if let Err(e) = create_day_ledger_table(&conn) {
+                eprintln!("Failed to create day ledger table: {}", e);
+            }
+            if let Err(e) = create_parking_ledger_table(&conn) {
+                eprintln!("Failed to create parking ledger table: {}", e);
+            }
+            if let Err(e) = create_scheduler_config_table(&conn) {
+                eprintln!("Failed to create scheduler config table: {}", e);
+            }
+            if let Err(e) = create_coverage_rules_table(&conn) {
+                eprintln!("Failed to create coverage rules table: {}", e);
+            }
+            if let Err(e) = create_teams_table(&conn) {
+                eprintln!("Failed to create teams table: {}", e);
+            }
+            if let Err(e) = create_locations_table(&conn) {
+                eprintln!("Failed to create locations table: {}", e);
+            }
+            if let Err(e) = create_desks_table(&conn) {
+                eprintln!("Failed to create desks table: {}", e);
+            }
+            if let Err(e) = create_meeting_rooms_table(&conn) {
+                eprintln!("Failed to create meeting rooms table: {}", e);
+            }
+            if let Err(e) = create_bookings_table(&conn) {
+                eprintln!("Failed to create bookings table: {}", e);
+            }
+            if let Err(e) = create_pair_constraints_table(&conn) {
+                eprintln!("Failed to create pair constraints table: {}", e);
+            }
+            if let Err(e) = create_closures_table(&conn) {
+                eprintln!("Failed to create closures table: {}", e);
+            }
+            if let Err(e) = create_leave_table(&conn) {
+                eprintln!("Failed to create leave table: {}", e);
+            }
         }
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
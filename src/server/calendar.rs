@@ -0,0 +1,226 @@
+use crate::server::schema::{
+    DatedSchedule, Employee, MonthlySchedule, ScheduleResult, ScheduleWeek, Weekday,
+};
+use chrono::{Datelike, NaiveDate};
+
+pub fn weekday_of(date: NaiveDate) -> Weekday {
+    match date.weekday() {
+        chrono::Weekday::Mon => Weekday::Monday,
+        chrono::Weekday::Tue => Weekday::Tuesday,
+        chrono::Weekday::Wed => Weekday::Wednesday,
+        chrono::Weekday::Thu => Weekday::Thursday,
+        chrono::Weekday::Fri => Weekday::Friday,
+        chrono::Weekday::Sat => Weekday::Saturday,
+        chrono::Weekday::Sun => Weekday::Sunday,
+    }
+}
+
+// Working dates of the month grouped by calendar week. The first and last weeks are
+// partial when the month starts or ends mid-week.
+pub fn month_weeks(year: i32, month: u32, work_days: &[Weekday]) -> Vec<Vec<NaiveDate>> {
+    let mut weeks: Vec<Vec<NaiveDate>> = Vec::new();
+    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return weeks;
+    };
+
+    let mut current_week = None;
+    for date in first.iter_days().take_while(|d| d.month() == month) {
+        if !work_days.contains(&weekday_of(date)) {
+            continue;
+        }
+        let week = date.iso_week();
+        if current_week != Some(week) {
+            current_week = Some(week);
+            weeks.push(Vec::new());
+        }
+        if let Some(dates) = weeks.last_mut() {
+            dates.push(date);
+        }
+    }
+    weeks
+}
+
+// Date falling on `day` within the week, if that day is inside the month
+pub fn date_for(week_dates: &[NaiveDate], day: &Weekday) -> Option<NaiveDate> {
+    week_dates
+        .iter()
+        .copied()
+        .find(|date| weekday_of(*date) == *day)
+}
+
+// Keeps only the weekdays of a weekly pattern that fall inside the month
+pub fn restrict_to_dates(schedule: &mut MonthlySchedule, week_dates: &[NaiveDate]) {
+    schedule.retain(|day, _| date_for(week_dates, day).is_some());
+}
+
+pub fn to_dated(weeks: &[ScheduleWeek]) -> DatedSchedule {
+    let mut dated = DatedSchedule::new();
+    for week in weeks {
+        for date in &week.dates {
+            let ids = week
+                .result
+                .schedule
+                .get(&weekday_of(*date))
+                .map(|emps| emps.iter().map(|e| e.id).collect())
+                .unwrap_or_default();
+            dated.insert(*date, ids);
+        }
+    }
+    dated
+}
+
+// Rebuilds the weeks of a month from stored dates. Employees who no longer exist are dropped.
+pub fn weeks_from_dated(
+    year: i32,
+    month: u32,
+    work_days: &[Weekday],
+    dated: &DatedSchedule,
+    employees: &[Employee],
+) -> Vec<ScheduleWeek> {
+    month_weeks(year, month, work_days)
+        .into_iter()
+        .map(|dates| {
+            let mut schedule = MonthlySchedule::new();
+            for date in &dates {
+                let day = weekday_of(*date);
+                let day_employees = dated
+                    .get(date)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| employees.iter().find(|e| e.id == *id).cloned())
+                    .collect();
+                schedule.insert(day, day_employees);
+            }
+            ScheduleWeek {
+                dates,
+                result: ScheduleResult {
+                    schedule,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+// The same weekly pattern on every week of the month, for schedules saved before dates were stored
+pub fn weeks_from_pattern(
+    year: i32,
+    month: u32,
+    work_days: &[Weekday],
+    pattern: &MonthlySchedule,
+) -> Vec<ScheduleWeek> {
+    month_weeks(year, month, work_days)
+        .into_iter()
+        .map(|dates| {
+            let mut schedule = pattern.clone();
+            restrict_to_dates(&mut schedule, &dates);
+            ScheduleWeek {
+                dates,
+                result: ScheduleResult {
+                    schedule,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+// Weekly pattern kept as the month's history: the last full week, being the most recent
+pub fn month_pattern(weeks: &[ScheduleWeek]) -> MonthlySchedule {
+    let full_week = weeks.iter().map(|week| week.dates.len()).max().unwrap_or(0);
+    weeks
+        .iter()
+        .rev()
+        .find(|week| week.dates.len() == full_week)
+        .map(|week| week.result.schedule.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::test_support::employee;
+
+    const WORK_DAYS: [Weekday; 5] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+    ];
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn days_of(weeks: &[Vec<NaiveDate>]) -> Vec<Vec<u32>> {
+        weeks
+            .iter()
+            .map(|dates| dates.iter().map(|date| date.day()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn month_starting_and_ending_mid_week_has_partial_weeks() {
+        // 1 April 2026 is a Wednesday and 30 April a Thursday
+        let weeks = month_weeks(2026, 4, &WORK_DAYS);
+
+        let days = days_of(&weeks);
+        assert_eq!(days.len(), 5);
+        assert_eq!(days[0], vec![1, 2, 3]);
+        assert_eq!(days[1], vec![6, 7, 8, 9, 10]);
+        assert_eq!(days[4], vec![27, 28, 29, 30]);
+    }
+
+    #[test]
+    fn month_starting_on_a_day_off_skips_that_week() {
+        // 1 November 2026 is a Sunday, so the first working week starts on the 2nd and the
+        // last holds only Monday the 30th
+        let weeks = month_weeks(2026, 11, &WORK_DAYS);
+
+        let days = days_of(&weeks);
+        assert_eq!(days.len(), 5);
+        assert_eq!(days[0], vec![2, 3, 4, 5, 6]);
+        assert_eq!(days[4], vec![30]);
+    }
+
+    #[test]
+    fn dated_schedule_round_trips_through_weeks() {
+        let employees: Vec<Employee> = (1..=4).map(|id| employee(id, 2)).collect();
+        let pattern: MonthlySchedule = WORK_DAYS
+            .iter()
+            .enumerate()
+            .map(|(index, day)| {
+                let present = vec![
+                    employees[index % 4].clone(),
+                    employees[(index + 1) % 4].clone(),
+                ];
+                (day.clone(), present)
+            })
+            .collect();
+        let weeks = weeks_from_pattern(2026, 11, &WORK_DAYS, &pattern);
+
+        let dated = to_dated(&weeks);
+        assert_eq!(dated.len(), 21);
+        assert_eq!(dated.keys().next(), Some(&date(2026, 11, 2)));
+        assert_eq!(dated.keys().last(), Some(&date(2026, 11, 30)));
+        assert_eq!(dated[&date(2026, 11, 30)], vec![1, 2]);
+        assert_eq!(
+            weeks_from_dated(2026, 11, &WORK_DAYS, &dated, &employees),
+            weeks
+        );
+    }
+
+    #[test]
+    fn weeks_from_dated_drops_employees_no_longer_on_file() {
+        let mut dated = DatedSchedule::new();
+        dated.insert(date(2026, 11, 30), vec![1, 2]);
+
+        let weeks = weeks_from_dated(2026, 11, &WORK_DAYS, &dated, &[employee(2, 2)]);
+
+        let last = weeks.last().unwrap();
+        assert_eq!(last.dates, vec![date(2026, 11, 30)]);
+        let monday = &last.result.schedule[&Weekday::Monday];
+        assert_eq!(monday.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2]);
+    }
+}
@@ -1,6 +1,14 @@
-use crate::server::schema::{Employee, MonthlySchedule, Role, Sex, Weekday};
+use crate::server::calendar::weekday_of;
+use crate::server::schema::{
+    Booking, Closure, CoverageRule, DatedSchedule, DayLedger, Desk, DeskAttribute, Employee,
+    EmployeeId, Leave, LeaveKind, Location, MeetingRoom, MonthlySchedule, PairConstraint, PairKind,
+    ParkingLedger, ParkingPlan, ParkingTally, Role, ScheduleConstraints, SchedulerConfig, Sex,
+    Team, Weekday,
+};
 use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime};
 use rusqlite::{params, Connection, Result as SqliteResult};
+use std::collections::BTreeMap;
 
 // pub fn establish_connection() -> Result<Connection> {
 //     let conn = Connection::open("employees.db")?;
@@ -36,17 +44,39 @@ pub fn create_employee_table(conn: &Connection) -> SqliteResult<()> {
             role TEXT NOT NULL,
             required_days INTEGER NOT NULL,
             fixed_days TEXT,  -- Store as JSON
-            is_nsp INTEGER NOT NULL
+            is_nsp INTEGER NOT NULL,
+            preferred_days TEXT,  -- JSON
+            unavailable_days TEXT,  -- JSON
+            team_id INTEGER,  -- NULL = no team
+            location_id INTEGER,  -- NULL = no home location
+            attends_location_id INTEGER,  -- NULL = attends their home location
+            desk_needs TEXT,  -- JSON
+            needs_parking INTEGER NOT NULL DEFAULT 0
         )",
         [],
     )?;
+    add_column_if_missing(conn, "employees", "preferred_days", "TEXT")?;
+    add_column_if_missing(conn, "employees", "unavailable_days", "TEXT")?;
+    add_column_if_missing(conn, "employees", "team_id", "INTEGER")?;
+    add_column_if_missing(conn, "employees", "location_id", "INTEGER")?;
+    add_column_if_missing(conn, "employees", "attends_location_id", "INTEGER")?;
+    add_column_if_missing(conn, "employees", "desk_needs", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "employees",
+        "needs_parking",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
     Ok(())
 }
 
 pub fn insert_employee(conn: &Connection, employee: &Employee) -> SqliteResult<()> {
     let fixed_days_json = serde_json::to_string(&employee.fixed_days).unwrap();
+    let preferred_days_json = serde_json::to_string(&employee.preferred_days).unwrap();
+    let unavailable_days_json = serde_json::to_string(&employee.unavailable_days).unwrap();
+    let desk_needs_json = serde_json::to_string(&employee.desk_needs).unwrap();
     conn.execute(
-        "INSERT INTO employees (id, name, sex, role, required_days, fixed_days, is_nsp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO employees (id, name, sex, role, required_days, fixed_days, is_nsp, preferred_days, unavailable_days, team_id, location_id, attends_location_id, desk_needs, needs_parking) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
         params![
             employee.id,
             employee.name,
@@ -54,7 +84,14 @@ pub fn insert_employee(conn: &Connection, employee: &Employee) -> SqliteResult<(
             employee.role.to_string(),
             employee.required_days,
             fixed_days_json,
-            employee.is_nsp as i32
+            employee.is_nsp as i32,
+            preferred_days_json,
+            unavailable_days_json,
+            employee.team_id,
+            employee.location_id,
+            employee.attends_location_id,
+            desk_needs_json,
+            employee.needs_parking as i32
         ],
     )?;
     Ok(())
@@ -66,15 +103,25 @@ pub fn insert_employee_with_auto_id(
     employee: &Employee,
 ) -> SqliteResult<Employee> {
     let fixed_days_json = serde_json::to_string(&employee.fixed_days).unwrap();
+    let preferred_days_json = serde_json::to_string(&employee.preferred_days).unwrap();
+    let unavailable_days_json = serde_json::to_string(&employee.unavailable_days).unwrap();
+    let desk_needs_json = serde_json::to_string(&employee.desk_needs).unwrap();
     conn.execute(
-        "INSERT INTO employees (name, sex, role, required_days, fixed_days, is_nsp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO employees (name, sex, role, required_days, fixed_days, is_nsp, preferred_days, unavailable_days, team_id, location_id, attends_location_id, desk_needs, needs_parking) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         params![
             employee.name,
             employee.sex.to_string(),
             employee.role.to_string(),
             employee.required_days,
             fixed_days_json,
-            employee.is_nsp as i32
+            employee.is_nsp as i32,
+            preferred_days_json,
+            unavailable_days_json,
+            employee.team_id,
+            employee.location_id,
+            employee.attends_location_id,
+            desk_needs_json,
+            employee.needs_parking as i32
         ],
     )?;
 
@@ -88,13 +135,23 @@ pub fn insert_employee_with_auto_id(
         required_days: employee.required_days,
         fixed_days: employee.fixed_days.clone(),
         is_nsp: employee.is_nsp,
+        preferred_days: employee.preferred_days.clone(),
+        unavailable_days: employee.unavailable_days.clone(),
+        team_id: employee.team_id,
+        location_id: employee.location_id,
+        attends_location_id: employee.attends_location_id,
+        desk_needs: employee.desk_needs.clone(),
+        needs_parking: employee.needs_parking,
     })
 }
 
 pub fn update_employee(conn: &Connection, employee: &Employee) -> SqliteResult<()> {
     let fixed_days_json = serde_json::to_string(&employee.fixed_days).unwrap();
+    let preferred_days_json = serde_json::to_string(&employee.preferred_days).unwrap();
+    let unavailable_days_json = serde_json::to_string(&employee.unavailable_days).unwrap();
+    let desk_needs_json = serde_json::to_string(&employee.desk_needs).unwrap();
     conn.execute(
-        "UPDATE employees SET name = ?2, sex = ?3, role = ?4, required_days = ?5, fixed_days = ?6, is_nsp = ?7 WHERE id = ?1",
+        "UPDATE employees SET name = ?2, sex = ?3, role = ?4, required_days = ?5, fixed_days = ?6, is_nsp = ?7, preferred_days = ?8, unavailable_days = ?9, team_id = ?10, location_id = ?11, attends_location_id = ?12, desk_needs = ?13, needs_parking = ?14 WHERE id = ?1",
         params![
             employee.id,
             employee.name,
@@ -102,20 +159,46 @@ pub fn update_employee(conn: &Connection, employee: &Employee) -> SqliteResult<(
             employee.role.to_string(),
             employee.required_days,
             fixed_days_json,
-            employee.is_nsp as i32
+            employee.is_nsp as i32,
+            preferred_days_json,
+            unavailable_days_json,
+            employee.team_id,
+            employee.location_id,
+            employee.attends_location_id,
+            desk_needs_json,
+            employee.needs_parking as i32
         ],
     )?;
     Ok(())
 }
 
 pub fn delete_employee(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM day_ledger WHERE employee_id = ?1", params![id])?;
+    conn.execute(
+        "DELETE FROM parking_ledger WHERE employee_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM leave WHERE employee_id = ?1", params![id])?;
+    conn.execute(
+        "DELETE FROM pair_constraints WHERE first_id = ?1 OR second_id = ?1",
+        params![id],
+    )?;
+    // Ids can be handed out again, so the employee leaves every meeting they were invited to
+    conn.execute(
+        "UPDATE bookings SET attendees = (
+            SELECT json_group_array(value) FROM json_each(bookings.attendees) WHERE value != ?1
+         )
+         WHERE EXISTS (SELECT 1 FROM json_each(bookings.attendees) WHERE value = ?1)",
+        params![id],
+    )?;
     conn.execute("DELETE FROM employees WHERE id = ?1", params![id])?;
     Ok(())
 }
 
 pub fn get_all_employees(conn: &Connection) -> SqliteResult<Vec<Employee>> {
-    let mut stmt = conn
-        .prepare("SELECT id, name, sex, role, required_days, fixed_days, is_nsp FROM employees")?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, sex, role, required_days, fixed_days, is_nsp, preferred_days, unavailable_days, team_id, location_id, attends_location_id, desk_needs, needs_parking FROM employees",
+    )?;
     let employee_iter = stmt.query_map([], |row| {
         let id: usize = row.get(0)?;
         let name: String = row.get(1)?;
@@ -124,34 +207,31 @@ pub fn get_all_employees(conn: &Connection) -> SqliteResult<Vec<Employee>> {
         let required_days: u8 = row.get(4)?;
         let fixed_days_json: String = row.get(5)?;
         let is_nsp: i32 = row.get(6)?;
+        // NULL for employees saved before these columns existed
+        let preferred_days_json: Option<String> = row.get(7)?;
+        let unavailable_days_json: Option<String> = row.get(8)?;
+        let team_id: Option<usize> = row.get(9)?;
+        let location_id: Option<usize> = row.get(10)?;
+        let attends_location_id: Option<usize> = row.get(11)?;
+        let desk_needs_json: Option<String> = row.get(12)?;
+        let needs_parking: i32 = row.get(13)?;
 
         let sex = match sex_str.as_str() {
             "Male" => Sex::Male,
             "Female" => Sex::Female,
             _ => Sex::Male, // Or handle the error/unknown case appropriately
         };
-        let role = match role_str.as_str() {
-            "Human Resource Manager" => Role::HR,
-            "AI-LLM Engineer" => Role::AiLlmEngineer,
-            "Social Media Marketing" => Role::SocialMediaMarketing,
-            // "Marketing Manager" => Role::MarketingManager,
-            "IT Support" => Role::ITSupport,
-            "Machine Learning Engineer" => Role::MLEngineer,
-            "Data Scientist" => Role::DataScientist,
-            "Data Analyst" => Role::DataAnalyst,
-            "Full-stack Engineer" => Role::FullStackEngineer,
-            "Backend Engineer" => Role::BackendEngineer,
-            "Frontend Engineer" => Role::FrontendEngineer,
-            "Blockchain Engineer" => Role::BlockchainEngineer,
-            "QA Engineer" => Role::QaEngineer,
-            "Project Manager" => Role::ProjectManager,
-            "UI/UX Designer" => Role::UiUxDesigner,
-            "Mobile Engineer" => Role::MobileEngineer,
-            "DevOps Engineer" => Role::DevOpsEngineer,
-            "Operations Manager" => Role::OperationsManager,
-            _ => Role::FullStackEngineer, // Or handle the error/unknown case appropriately
-        };
+        let role = parse_role(&role_str);
         let fixed_days: Vec<Weekday> = serde_json::from_str(&fixed_days_json).unwrap_or_default();
+        let preferred_days: Vec<Weekday> = preferred_days_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let unavailable_days: Vec<Weekday> = unavailable_days_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let desk_needs: Vec<DeskAttribute> = desk_needs_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
 
         Ok(Employee {
             id,
@@ -161,6 +241,13 @@ pub fn get_all_employees(conn: &Connection) -> SqliteResult<Vec<Employee>> {
             required_days,
             fixed_days,
             is_nsp: is_nsp != 0,
+            preferred_days,
+            unavailable_days,
+            team_id,
+            location_id,
+            attends_location_id,
+            desk_needs,
+            needs_parking: needs_parking != 0,
         })
     })?;
 
@@ -171,6 +258,45 @@ pub fn get_all_employees(conn: &Connection) -> SqliteResult<Vec<Employee>> {
     Ok(employees)
 }
 
+// Weekdays are stored by their display name
+fn parse_weekday(weekday_str: &str) -> Option<Weekday> {
+    match weekday_str {
+        "Monday" => Some(Weekday::Monday),
+        "Tuesday" => Some(Weekday::Tuesday),
+        "Wednesday" => Some(Weekday::Wednesday),
+        "Thursday" => Some(Weekday::Thursday),
+        "Friday" => Some(Weekday::Friday),
+        "Saturday" => Some(Weekday::Saturday),
+        "Sunday" => Some(Weekday::Sunday),
+        _ => None,
+    }
+}
+
+// Roles are stored by their display name
+fn parse_role(role_str: &str) -> Role {
+    match role_str {
+        "Human Resource Manager" => Role::HR,
+        "AI-LLM Engineer" => Role::AiLlmEngineer,
+        "Social Media Marketing" => Role::SocialMediaMarketing,
+        // "Marketing Manager" => Role::MarketingManager,
+        "IT Support" => Role::ITSupport,
+        "Machine Learning Engineer" => Role::MLEngineer,
+        "Data Scientist" => Role::DataScientist,
+        "Data Analyst" => Role::DataAnalyst,
+        "Full-stack Engineer" => Role::FullStackEngineer,
+        "Backend Engineer" => Role::BackendEngineer,
+        "Frontend Engineer" => Role::FrontendEngineer,
+        "Blockchain Engineer" => Role::BlockchainEngineer,
+        "QA Engineer" => Role::QaEngineer,
+        "Project Manager" => Role::ProjectManager,
+        "UI/UX Designer" => Role::UiUxDesigner,
+        "Mobile Engineer" => Role::MobileEngineer,
+        "DevOps Engineer" => Role::DevOpsEngineer,
+        "Operations Manager" => Role::OperationsManager,
+        _ => Role::FullStackEngineer, // Or handle the error/unknown case appropriately
+    }
+}
+
 pub fn create_schedules_table(conn: &Connection) -> SqliteResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS schedules (
@@ -178,11 +304,34 @@ pub fn create_schedules_table(conn: &Connection) -> SqliteResult<()> {
             year INTEGER NOT NULL,
             month INTEGER NOT NULL,
             schedule_data TEXT NOT NULL,  -- JSON serialized MonthlySchedule
+            seed INTEGER,                 -- RNG seed the schedule was generated with
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             UNIQUE(year, month)
         )",
         [],
     )?;
+    add_column_if_missing(conn, "schedules", "seed", "INTEGER")?;
+    Ok(())
+}
+
+// Brings tables created by older versions of the app up to date
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> SqliteResult<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
     Ok(())
 }
 
@@ -191,11 +340,13 @@ pub fn save_schedule_to_db(
     year: i32,
     month: u32,
     schedule: &MonthlySchedule,
+    seed: Option<u64>,
 ) -> SqliteResult<()> {
     let schedule_json = serde_json::to_string(schedule).unwrap();
+    // SQLite integers are signed; the seed's bits are stored as-is
     conn.execute(
-        "INSERT OR REPLACE INTO schedules (year, month, schedule_data) VALUES (?1, ?2, ?3)",
-        params![year, month, schedule_json],
+        "INSERT OR REPLACE INTO schedules (year, month, schedule_data, seed) VALUES (?1, ?2, ?3, ?4)",
+        params![year, month, schedule_json, seed.map(|s| s as i64)],
     )?;
     Ok(())
 }
@@ -222,13 +373,875 @@ pub fn load_schedule_from_db(
     }
 }
 
+pub fn load_schedule_seed(conn: &Connection, year: i32, month: u32) -> SqliteResult<Option<u64>> {
+    let mut stmt = conn.prepare(
+        "SELECT seed FROM schedules WHERE year = ?1 AND month = ?2 ORDER BY created_at DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query_map(params![year, month], |row| row.get::<_, Option<i64>>(0))?;
+
+    match rows.next() {
+        Some(row) => Ok(row?.map(|s| s as u64)),
+        None => Ok(None),
+    }
+}
+
+// Per-date assignments of a month; the `schedules` row keeps its seed and weekly pattern
+pub fn create_schedule_days_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schedule_days (
+            year INTEGER NOT NULL,
+            month INTEGER NOT NULL,
+            date TEXT NOT NULL,  -- YYYY-MM-DD
+            employee_id INTEGER NOT NULL,
+            PRIMARY KEY (date, employee_id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn save_schedule_days(
+    conn: &Connection,
+    year: i32,
+    month: u32,
+    dated: &DatedSchedule,
+) -> SqliteResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM schedule_days WHERE year = ?1 AND month = ?2",
+        params![year, month],
+    )?;
+    for (date, employee_ids) in dated {
+        let date = date.format("%Y-%m-%d").to_string();
+        for employee_id in employee_ids {
+            tx.execute(
+                "INSERT OR REPLACE INTO schedule_days (year, month, date, employee_id) VALUES (?1, ?2, ?3, ?4)",
+                params![year, month, date, employee_id],
+            )?;
+        }
+    }
+
+    // The month's ledger rows are rebuilt from the same dates, so re-saving never double counts
+    tx.execute(
+        "DELETE FROM day_ledger WHERE year = ?1 AND month = ?2",
+        params![year, month],
+    )?;
+    let mut month_counts: BTreeMap<(EmployeeId, String), usize> = BTreeMap::new();
+    for (date, employee_ids) in dated {
+        let day = weekday_of(*date);
+        for employee_id in employee_ids {
+            *month_counts
+                .entry((*employee_id, day.to_string()))
+                .or_insert(0) += 1;
+        }
+    }
+    for ((employee_id, weekday), days) in month_counts {
+        tx.execute(
+            "INSERT INTO day_ledger (employee_id, year, month, weekday, days) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![employee_id, year, month, weekday, days],
+        )?;
+    }
+    tx.commit()
+}
+
+pub fn create_day_ledger_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS day_ledger (
+            employee_id INTEGER NOT NULL,
+            year INTEGER NOT NULL,
+            month INTEGER NOT NULL,
+            weekday TEXT NOT NULL,  -- display name
+            days INTEGER NOT NULL,  -- office days on that weekday in the month
+            PRIMARY KEY (employee_id, year, month, weekday)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Weekday totals per employee over the saved months from `from` to `to`, both included
+pub fn load_day_ledger(
+    conn: &Connection,
+    from: (i32, u32),
+    to: (i32, u32),
+) -> SqliteResult<DayLedger> {
+    let mut stmt = conn.prepare(
+        "SELECT employee_id, weekday, SUM(days) FROM day_ledger
+         WHERE year * 12 + month BETWEEN ?1 AND ?2
+         GROUP BY employee_id, weekday",
+    )?;
+    let row_iter = stmt.query_map(
+        params![from.0 * 12 + from.1 as i32, to.0 * 12 + to.1 as i32],
+        |row| {
+            Ok((
+                row.get::<_, EmployeeId>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, usize>(2)?,
+            ))
+        },
+    )?;
+
+    let mut ledger = DayLedger::new();
+    for row in row_iter {
+        let (employee_id, weekday, days) = row?;
+        if let Some(day) = parse_weekday(&weekday) {
+            ledger.entry(employee_id).or_default().insert(day, days);
+        }
+    }
+    Ok(ledger)
+}
+
+pub fn create_parking_ledger_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS parking_ledger (
+            employee_id INTEGER NOT NULL,
+            year INTEGER NOT NULL,
+            month INTEGER NOT NULL,
+            requested INTEGER NOT NULL,  -- office days a space was needed
+            parked INTEGER NOT NULL,     -- office days one was given
+            PRIMARY KEY (employee_id, year, month)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Replaces the month's rows, so re-saving a schedule never double counts
+pub fn save_parking_ledger(
+    conn: &Connection,
+    year: i32,
+    month: u32,
+    plan: &ParkingPlan,
+) -> SqliteResult<()> {
+    let mut month_tallies: BTreeMap<EmployeeId, ParkingTally> = BTreeMap::new();
+    for day in plan.values() {
+        for employee in &day.parked {
+            let tally = month_tallies.entry(employee.id).or_default();
+            tally.requested += 1;
+            tally.parked += 1;
+        }
+        for employee in &day.missed {
+            month_tallies.entry(employee.id).or_default().requested += 1;
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM parking_ledger WHERE year = ?1 AND month = ?2",
+        params![year, month],
+    )?;
+    for (employee_id, tally) in month_tallies {
+        tx.execute(
+            "INSERT INTO parking_ledger (employee_id, year, month, requested, parked) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![employee_id, year, month, tally.requested, tally.parked],
+        )?;
+    }
+    tx.commit()
+}
+
+// Parking totals per employee over the saved months from `from` to `to`, both included
+pub fn load_parking_ledger(
+    conn: &Connection,
+    from: (i32, u32),
+    to: (i32, u32),
+) -> SqliteResult<ParkingLedger> {
+    let mut stmt = conn.prepare(
+        "SELECT employee_id, SUM(requested), SUM(parked) FROM parking_ledger
+         WHERE year * 12 + month BETWEEN ?1 AND ?2
+         GROUP BY employee_id",
+    )?;
+    let row_iter = stmt.query_map(
+        params![from.0 * 12 + from.1 as i32, to.0 * 12 + to.1 as i32],
+        |row| {
+            Ok((
+                row.get::<_, EmployeeId>(0)?,
+                ParkingTally {
+                    requested: row.get(1)?,
+                    parked: row.get(2)?,
+                },
+            ))
+        },
+    )?;
+
+    let mut ledger = ParkingLedger::new();
+    for row in row_iter {
+        let (employee_id, tally) = row?;
+        ledger.insert(employee_id, tally);
+    }
+    Ok(ledger)
+}
+
+// None when nothing was stored per date for the month
+pub fn load_schedule_days(
+    conn: &Connection,
+    year: i32,
+    month: u32,
+) -> SqliteResult<Option<DatedSchedule>> {
+    let mut stmt = conn.prepare(
+        "SELECT date, employee_id FROM schedule_days WHERE year = ?1 AND month = ?2 ORDER BY date, employee_id",
+    )?;
+    let row_iter = stmt.query_map(params![year, month], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, usize>(1)?))
+    })?;
+
+    let mut dated = DatedSchedule::new();
+    for row in row_iter {
+        let (date, employee_id) = row?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            dated.entry(date).or_default().push(employee_id);
+        }
+    }
+    Ok((!dated.is_empty()).then_some(dated))
+}
+
+pub fn create_scheduler_config_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduler_config (
+            id INTEGER PRIMARY KEY CHECK (id = 1), -- single settings row
+            config_data TEXT NOT NULL  -- JSON serialized SchedulerConfig
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn save_scheduler_config(conn: &Connection, config: &SchedulerConfig) -> SqliteResult<()> {
+    let config_json = serde_json::to_string(config).unwrap();
+    conn.execute(
+        "INSERT OR REPLACE INTO scheduler_config (id, config_data) VALUES (1, ?1)",
+        params![config_json],
+    )?;
+    Ok(())
+}
+
+// Falls back to the default config when nothing has been saved yet
+pub fn load_scheduler_config(conn: &Connection) -> SqliteResult<SchedulerConfig> {
+    let mut stmt = conn.prepare("SELECT config_data FROM scheduler_config WHERE id = 1")?;
+    let mut rows = stmt.query_map([], |row| {
+        let data: String = row.get(0)?;
+        Ok(data)
+    })?;
+
+    if let Some(row) = rows.next() {
+        let data = row?;
+        Ok(serde_json::from_str(&data).unwrap_or_default())
+    } else {
+        Ok(SchedulerConfig::default())
+    }
+}
+
+pub fn create_coverage_rules_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS coverage_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            role TEXT NOT NULL,
+            min_count INTEGER,
+            max_count INTEGER,
+            days TEXT NOT NULL  -- JSON, empty list = every day
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_coverage_rule(conn: &Connection, rule: &CoverageRule) -> SqliteResult<CoverageRule> {
+    let days_json = serde_json::to_string(&rule.days).unwrap();
+    conn.execute(
+        "INSERT INTO coverage_rules (role, min_count, max_count, days) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            rule.role.to_string(),
+            rule.min_count,
+            rule.max_count,
+            days_json
+        ],
+    )?;
+
+    Ok(CoverageRule {
+        id: conn.last_insert_rowid() as usize,
+        ..rule.clone()
+    })
+}
+
+pub fn delete_coverage_rule(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM coverage_rules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_coverage_rules(conn: &Connection) -> SqliteResult<Vec<CoverageRule>> {
+    let mut stmt =
+        conn.prepare("SELECT id, role, min_count, max_count, days FROM coverage_rules")?;
+    let rule_iter = stmt.query_map([], |row| {
+        let role_str: String = row.get(1)?;
+        let days_json: String = row.get(4)?;
+
+        Ok(CoverageRule {
+            id: row.get(0)?,
+            role: parse_role(&role_str),
+            min_count: row.get(2)?,
+            max_count: row.get(3)?,
+            days: serde_json::from_str(&days_json).unwrap_or_default(),
+        })
+    })?;
+
+    let mut rules = Vec::new();
+    for rule in rule_iter {
+        rules.push(rule?);
+    }
+    Ok(rules)
+}
+
+pub fn create_teams_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS teams (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            colocation_days INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_team(conn: &Connection, team: &Team) -> SqliteResult<Team> {
+    conn.execute(
+        "INSERT INTO teams (name, colocation_days) VALUES (?1, ?2)",
+        params![team.name, team.colocation_days],
+    )?;
+
+    Ok(Team {
+        id: conn.last_insert_rowid() as usize,
+        ..team.clone()
+    })
+}
+
+// Members stay on file without a team
+pub fn delete_team(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute(
+        "UPDATE employees SET team_id = NULL WHERE team_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM teams WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_teams(conn: &Connection) -> SqliteResult<Vec<Team>> {
+    let mut stmt = conn.prepare("SELECT id, name, colocation_days FROM teams ORDER BY name")?;
+    let team_iter = stmt.query_map([], |row| {
+        Ok(Team {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            colocation_days: row.get(2)?,
+        })
+    })?;
+
+    let mut teams = Vec::new();
+    for team in team_iter {
+        teams.push(team?);
+    }
+    Ok(teams)
+}
+
+pub fn create_locations_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS locations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            capacity INTEGER  -- NULL = unlimited
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_location(conn: &Connection, location: &Location) -> SqliteResult<Location> {
+    conn.execute(
+        "INSERT INTO locations (name, capacity) VALUES (?1, ?2)",
+        params![location.name, location.capacity],
+    )?;
+
+    Ok(Location {
+        id: conn.last_insert_rowid() as usize,
+        ..location.clone()
+    })
+}
+
+// Staff stay on file without a location
+pub fn delete_location(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute(
+        "UPDATE employees SET location_id = NULL WHERE location_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "UPDATE employees SET attends_location_id = NULL WHERE attends_location_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "UPDATE desks SET location_id = NULL WHERE location_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "UPDATE meeting_rooms SET location_id = NULL WHERE location_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM locations WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_locations(conn: &Connection) -> SqliteResult<Vec<Location>> {
+    let mut stmt = conn.prepare("SELECT id, name, capacity FROM locations ORDER BY name")?;
+    let location_iter = stmt.query_map([], |row| {
+        Ok(Location {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            capacity: row.get(2)?,
+        })
+    })?;
+
+    let mut locations = Vec::new();
+    for location in location_iter {
+        locations.push(location?);
+    }
+    Ok(locations)
+}
+
+pub fn create_desks_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS desks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            floor TEXT NOT NULL,
+            zone TEXT NOT NULL,
+            location_id INTEGER,  -- NULL = any location
+            attributes TEXT NOT NULL  -- JSON
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_desk(conn: &Connection, desk: &Desk) -> SqliteResult<Desk> {
+    let attributes_json = serde_json::to_string(&desk.attributes).unwrap();
+    conn.execute(
+        "INSERT INTO desks (name, floor, zone, location_id, attributes) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![desk.name, desk.floor, desk.zone, desk.location_id, attributes_json],
+    )?;
+
+    Ok(Desk {
+        id: conn.last_insert_rowid() as usize,
+        ..desk.clone()
+    })
+}
+
+pub fn delete_desk(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM desks WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// Ordered by floor and zone, which is also the order desks are handed out in
+pub fn get_all_desks(conn: &Connection) -> SqliteResult<Vec<Desk>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, floor, zone, location_id, attributes FROM desks ORDER BY floor, zone, name",
+    )?;
+    let desk_iter = stmt.query_map([], |row| {
+        let attributes_json: String = row.get(5)?;
+        Ok(Desk {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            floor: row.get(2)?,
+            zone: row.get(3)?,
+            location_id: row.get(4)?,
+            attributes: serde_json::from_str(&attributes_json).unwrap_or_default(),
+        })
+    })?;
+
+    let mut desks = Vec::new();
+    for desk in desk_iter {
+        desks.push(desk?);
+    }
+    Ok(desks)
+}
+
+pub fn create_meeting_rooms_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS meeting_rooms (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            capacity INTEGER NOT NULL,
+            location_id INTEGER  -- NULL = any location
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_meeting_room(conn: &Connection, room: &MeetingRoom) -> SqliteResult<MeetingRoom> {
+    conn.execute(
+        "INSERT INTO meeting_rooms (name, capacity, location_id) VALUES (?1, ?2, ?3)",
+        params![room.name, room.capacity, room.location_id],
+    )?;
+
+    Ok(MeetingRoom {
+        id: conn.last_insert_rowid() as usize,
+        ..room.clone()
+    })
+}
+
+// The room's bookings go with it
+pub fn delete_meeting_room(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM bookings WHERE room_id = ?1", params![id])?;
+    conn.execute("DELETE FROM meeting_rooms WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_meeting_rooms(conn: &Connection) -> SqliteResult<Vec<MeetingRoom>> {
+    let mut stmt =
+        conn.prepare("SELECT id, name, capacity, location_id FROM meeting_rooms ORDER BY name")?;
+    let room_iter = stmt.query_map([], |row| {
+        Ok(MeetingRoom {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            capacity: row.get(2)?,
+            location_id: row.get(3)?,
+        })
+    })?;
+
+    let mut rooms = Vec::new();
+    for room in room_iter {
+        rooms.push(room?);
+    }
+    Ok(rooms)
+}
+
+pub fn create_bookings_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bookings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            date TEXT NOT NULL,        -- YYYY-MM-DD
+            start_time TEXT NOT NULL,  -- HH:MM
+            end_time TEXT NOT NULL,    -- HH:MM
+            attendees TEXT NOT NULL    -- JSON array of employee ids
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_booking(conn: &Connection, booking: &Booking) -> SqliteResult<Booking> {
+    let attendees_json = serde_json::to_string(&booking.attendees).unwrap();
+    conn.execute(
+        "INSERT INTO bookings (room_id, title, date, start_time, end_time, attendees) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            booking.room_id,
+            booking.title,
+            booking.date.format("%Y-%m-%d").to_string(),
+            booking.start.format("%H:%M").to_string(),
+            booking.end.format("%H:%M").to_string(),
+            attendees_json
+        ],
+    )?;
+
+    Ok(Booking {
+        id: conn.last_insert_rowid() as usize,
+        ..booking.clone()
+    })
+}
+
+pub fn delete_booking(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM bookings WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_bookings_for_month(
+    conn: &Connection,
+    year: i32,
+    month: u32,
+) -> SqliteResult<Vec<Booking>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, room_id, title, date, start_time, end_time, attendees FROM bookings
+         WHERE date LIKE ?1 ORDER BY date, start_time",
+    )?;
+    let booking_iter = stmt.query_map(params![format!("{:04}-{:02}-%", year, month)], |row| {
+        Ok((
+            row.get::<_, usize>(0)?,
+            row.get::<_, usize>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, String>(5)?,
+            row.get::<_, String>(6)?,
+        ))
+    })?;
+
+    let mut bookings = Vec::new();
+    for row in booking_iter {
+        let (id, room_id, title, date, start, end, attendees_json) = row?;
+        if let (Ok(date), Ok(start), Ok(end)) = (
+            NaiveDate::parse_from_str(&date, "%Y-%m-%d"),
+            NaiveTime::parse_from_str(&start, "%H:%M"),
+            NaiveTime::parse_from_str(&end, "%H:%M"),
+        ) {
+            bookings.push(Booking {
+                id,
+                room_id,
+                title,
+                date,
+                start,
+                end,
+                attendees: serde_json::from_str(&attendees_json).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(bookings)
+}
+
+pub fn create_pair_constraints_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pair_constraints (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            first_id INTEGER NOT NULL,
+            second_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,  -- 'together' or 'apart'
+            min_days INTEGER     -- together only
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_pair_constraint(
+    conn: &Connection,
+    pair: &PairConstraint,
+) -> SqliteResult<PairConstraint> {
+    let (kind, min_days) = match pair.kind {
+        PairKind::Together { min_days } => ("together", Some(min_days)),
+        PairKind::Apart => ("apart", None),
+    };
+    conn.execute(
+        "INSERT INTO pair_constraints (first_id, second_id, kind, min_days) VALUES (?1, ?2, ?3, ?4)",
+        params![pair.first_id, pair.second_id, kind, min_days],
+    )?;
+
+    Ok(PairConstraint {
+        id: conn.last_insert_rowid() as usize,
+        ..pair.clone()
+    })
+}
+
+pub fn delete_pair_constraint(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM pair_constraints WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_pair_constraints(conn: &Connection) -> SqliteResult<Vec<PairConstraint>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.id, p.first_id, p.second_id, p.kind, p.min_days, a.name, b.name
+         FROM pair_constraints p
+         JOIN employees a ON a.id = p.first_id
+         JOIN employees b ON b.id = p.second_id
+         ORDER BY p.id",
+    )?;
+    let pair_iter = stmt.query_map([], |row| {
+        let kind_str: String = row.get(3)?;
+        let min_days: Option<usize> = row.get(4)?;
+        let kind = match kind_str.as_str() {
+            "apart" => PairKind::Apart,
+            _ => PairKind::Together {
+                min_days: min_days.unwrap_or(1),
+            },
+        };
+
+        Ok(PairConstraint {
+            id: row.get(0)?,
+            first_id: row.get(1)?,
+            second_id: row.get(2)?,
+            kind,
+            first_name: row.get(5)?,
+            second_name: row.get(6)?,
+        })
+    })?;
+
+    let mut pairs = Vec::new();
+    for pair in pair_iter {
+        pairs.push(pair?);
+    }
+    Ok(pairs)
+}
+
+pub fn create_closures_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS closures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL UNIQUE,  -- YYYY-MM-DD
+            name TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// A date that is already closed keeps its row and takes the new name
+pub fn insert_closure(conn: &Connection, closure: &Closure) -> SqliteResult<Closure> {
+    let id = conn.query_row(
+        "INSERT INTO closures (date, name) VALUES (?1, ?2)
+         ON CONFLICT(date) DO UPDATE SET name = excluded.name
+         RETURNING id",
+        params![closure.date.format("%Y-%m-%d").to_string(), closure.name],
+        |row| row.get(0),
+    )?;
+
+    Ok(Closure {
+        id,
+        ..closure.clone()
+    })
+}
+
+pub fn delete_closure(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM closures WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_all_closures(conn: &Connection) -> SqliteResult<Vec<Closure>> {
+    let mut stmt = conn.prepare("SELECT id, date, name FROM closures ORDER BY date")?;
+    let closure_iter = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, usize>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut closures = Vec::new();
+    for closure in closure_iter {
+        let (id, date, name) = closure?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            closures.push(Closure { id, date, name });
+        }
+    }
+    Ok(closures)
+}
+
+pub fn create_leave_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS leave (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            start_date TEXT NOT NULL,  -- YYYY-MM-DD
+            end_date TEXT NOT NULL     -- YYYY-MM-DD, inclusive
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn insert_leave(conn: &Connection, leave: &Leave) -> SqliteResult<Leave> {
+    conn.execute(
+        "INSERT INTO leave (employee_id, kind, start_date, end_date) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            leave.employee_id,
+            leave.kind.to_string(),
+            leave.start.format("%Y-%m-%d").to_string(),
+            leave.end.format("%Y-%m-%d").to_string()
+        ],
+    )?;
+
+    Ok(Leave {
+        id: conn.last_insert_rowid() as usize,
+        ..leave.clone()
+    })
+}
+
+pub fn delete_leave(conn: &Connection, id: usize) -> SqliteResult<()> {
+    conn.execute("DELETE FROM leave WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+fn query_leave(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> SqliteResult<Vec<Leave>> {
+    let mut stmt = conn.prepare(sql)?;
+    let leave_iter = stmt.query_map(params, |row| {
+        Ok((
+            row.get::<_, usize>(0)?,
+            row.get::<_, usize>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+
+    let mut periods = Vec::new();
+    for row in leave_iter {
+        let (id, employee_id, kind_str, start, end) = row?;
+        let kind = match kind_str.as_str() {
+            "Sick" => LeaveKind::Sick,
+            "Training" => LeaveKind::Training,
+            _ => LeaveKind::Annual,
+        };
+        if let (Ok(start), Ok(end)) = (
+            NaiveDate::parse_from_str(&start, "%Y-%m-%d"),
+            NaiveDate::parse_from_str(&end, "%Y-%m-%d"),
+        ) {
+            periods.push(Leave {
+                id,
+                employee_id,
+                kind,
+                start,
+                end,
+            });
+        }
+    }
+    Ok(periods)
+}
+
+pub fn get_all_leave(conn: &Connection) -> SqliteResult<Vec<Leave>> {
+    query_leave(
+        conn,
+        "SELECT id, employee_id, kind, start_date, end_date FROM leave ORDER BY start_date",
+        [],
+    )
+}
+
+pub fn get_leave_for_employee(conn: &Connection, employee_id: usize) -> SqliteResult<Vec<Leave>> {
+    query_leave(
+        conn,
+        "SELECT id, employee_id, kind, start_date, end_date FROM leave WHERE employee_id = ?1 ORDER BY start_date",
+        params![employee_id],
+    )
+}
+
+// Gathers every stored constraint the scheduler needs for a generation run
+pub fn load_schedule_constraints(conn: &Connection) -> SqliteResult<ScheduleConstraints> {
+    Ok(ScheduleConstraints {
+        coverage_rules: get_all_coverage_rules(conn)?,
+        teams: get_all_teams(conn)?,
+        pairs: get_all_pair_constraints(conn)?,
+        locations: get_all_locations(conn)?,
+        closures: get_all_closures(conn)?,
+        leave: get_all_leave(conn)?,
+        ..Default::default()
+    })
+}
+
 // RESET METHODS
 pub fn delete_all_employees(conn: &Connection) -> SqliteResult<()> {
+    conn.execute("DELETE FROM day_ledger", [])?;
+    conn.execute("DELETE FROM parking_ledger", [])?;
+    conn.execute("DELETE FROM leave", [])?;
+    conn.execute("DELETE FROM pair_constraints", [])?;
+    conn.execute("DELETE FROM bookings", [])?;
     conn.execute("DELETE FROM employees", [])?;
     Ok(())
 }
 
 pub fn delete_all_schedules(conn: &Connection) -> SqliteResult<()> {
+    conn.execute("DELETE FROM day_ledger", [])?;
+    conn.execute("DELETE FROM parking_ledger", [])?;
+    conn.execute("DELETE FROM schedule_days", [])?;
     conn.execute("DELETE FROM schedules", [])?;
     Ok(())
 }
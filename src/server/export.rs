@@ -1,16 +1,16 @@
 use crate::{
     client::pages::settings_page,
-    server::schema::{Employee, MonthlySchedule, Weekday},
+    server::{
+        calendar::weekday_of,
+        schema::{DatedSeatPlan, Employee, Location, ParkingPlan, ScheduleWeek},
+    },
 };
-use chrono::Month;
+use chrono::{Month, NaiveDate};
 use dioxus::{
     logger::tracing::{error, info},
     // prelude::*,
 };
-use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
-};
+use std::{collections::HashSet, error::Error};
 
 use rust_xlsxwriter::*;
 
@@ -21,70 +21,72 @@ fn get_month_name(month: u32) -> String {
         .unwrap_or_else(|_| format!("Month_{}", month)) // Fallback for invalid month
 }
 
+// Office dates of the month in order, with who is in on each. Partial weeks only hold the
+// dates inside the month.
+fn month_days(weeks: &[ScheduleWeek]) -> Vec<(NaiveDate, Vec<Employee>)> {
+    weeks
+        .iter()
+        .flat_map(|week| {
+            week.dates.iter().map(|date| {
+                let employees = week
+                    .result
+                    .schedule
+                    .get(&weekday_of(*date))
+                    .cloned()
+                    .unwrap_or_default();
+                (*date, employees)
+            })
+        })
+        .collect()
+}
+
+// Everyone in the office on any of the days, by name
+fn employees_in(days: &[(NaiveDate, Vec<Employee>)]) -> Vec<&Employee> {
+    let mut employees: Vec<&Employee> = days
+        .iter()
+        .flat_map(|(_, employees)| employees)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    employees.sort_by(|a, b| a.name.cmp(&b.name));
+    employees
+}
+
+fn date_label(date: &NaiveDate) -> String {
+    date.format("%a %-d %b").to_string()
+}
+
+// One column per office date of the month
 pub fn generate_csv_data(
-    schedule: &MonthlySchedule,
+    weeks: &[ScheduleWeek],
     year: i32,
     month: u32,
 ) -> Result<(String, String), Box<dyn Error>> {
     let month_name = get_month_name(month);
     let filename = format!("office_schedule_{}_{}.csv", month_name, year);
-
-    let weekdays = [
-        Weekday::Monday,
-        Weekday::Tuesday,
-        Weekday::Wednesday,
-        Weekday::Thursday,
-        Weekday::Friday,
-    ];
+    let days = month_days(weeks);
 
     // --- Header Row 1 ---
     let header1_parts: Vec<String> = std::iter::once("Name".to_string())
-        .chain(weekdays.iter().map(|d| d.to_string()))
+        .chain(days.iter().map(|(date, _)| date_label(date)))
         .collect();
     let header1 = header1_parts.join(",");
 
     // --- Header Row 2 (Counts) ---
-    let counts: Vec<String> = weekdays
+    let counts: Vec<String> = days
         .iter()
-        .map(|day| {
-            let count = schedule.get(day).map_or(0, |emps| emps.len());
-            format!("{}", count)
-        })
+        .map(|(_, employees)| employees.len().to_string())
         .collect();
     // Add an empty cell at the beginning to align with the 'Name' column
     let header2 = format!(",{}", counts.join(",")); // Prepend comma for empty first cell
 
     // --- Data Rows ---
-
-    // 1. Collect all unique employees and sort them by name
-    let mut all_employee_refs: Vec<&Employee> = schedule
-        .values()
-        .flatten()
-        .collect::<HashSet<_>>() // Deduplicate Employee references
-        .into_iter()
-        .collect();
-    all_employee_refs.sort_by(|a, b| a.name.cmp(&b.name)); // Sort by name
-
-    // 2. Create assignment lookup: HashMap<Weekday, HashSet<EmployeeName>>
-    let assignments: HashMap<Weekday, HashSet<String>> = schedule
-        .iter()
-        .map(|(day, emps)| {
-            let names = emps.iter().map(|e| e.name.clone()).collect::<HashSet<_>>();
-            (day.clone(), names) // Clone Weekday for the key
-        })
-        .collect();
-
-    // 3. Generate data rows
-    let data_rows: Vec<String> = all_employee_refs
+    let data_rows: Vec<String> = employees_in(&days)
         .iter()
         .map(|emp| {
             let mut row_parts = vec![emp.name.clone()]; // Start row with employee name
-            for day in &weekdays {
-                // Check if this employee is assigned on this day
-                let is_assigned = assignments
-                    .get(day) // Get the set of names for the day
-                    .map_or(false, |names_on_day| names_on_day.contains(&emp.name)); // Check if emp's name is in the set
-
+            for (_, employees) in &days {
+                let is_assigned = employees.iter().any(|e| e.id == emp.id);
                 row_parts.push(if is_assigned {
                     "X".to_string()
                 } else {
@@ -173,27 +175,82 @@ pub async fn save_xlsx_with_dialog(
     }
 }
 
-// creating a formatted XLSX file that looks good
+// creating a formatted XLSX file that looks good, with one column per office date of the
+// month. With locations, the whole schedule is followed by one sheet per location holding
+// only the people who come in there. Office days show the person's desk when they have one,
+// otherwise an X. When anyone needs parking, a last sheet lists who parks on each date.
 pub fn generate_xlsx_data(
-    schedule: &MonthlySchedule,
+    weeks: &[ScheduleWeek],
+    locations: &[Location],
+    seats: &DatedSeatPlan,
+    parking: &ParkingPlan,
     year: i32,
     month: u32,
 ) -> Result<(String, Vec<u8>), Box<dyn Error>> {
     let month_name = get_month_name(month);
     let filename = format!("office_schedule_{}_{}.xlsx", month_name, year);
-
-    let weekdays = [
-        Weekday::Monday,
-        Weekday::Tuesday,
-        Weekday::Wednesday,
-        Weekday::Thursday,
-        Weekday::Friday,
-    ];
+    let days = month_days(weeks);
 
     // Create a new workbook
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet().set_name("Schedule")?;
+    write_schedule_sheet(worksheet, &days, seats)?;
+
+    let mut sheet_names = vec!["schedule".to_string()];
+    for location in locations {
+        let location_days: Vec<(NaiveDate, Vec<Employee>)> = days
+            .iter()
+            .map(|(date, employees)| {
+                let at_location = employees
+                    .iter()
+                    .filter(|e| e.office_location() == Some(location.id))
+                    .cloned()
+                    .collect();
+                (*date, at_location)
+            })
+            .collect();
+        let name = sheet_name(&location.name, &sheet_names);
+        sheet_names.push(name.to_lowercase());
+        let worksheet = workbook.add_worksheet().set_name(&name)?;
+        write_schedule_sheet(worksheet, &location_days, seats)?;
+    }
+    if parking
+        .values()
+        .any(|day| !day.parked.is_empty() || !day.missed.is_empty())
+    {
+        let name = sheet_name("Parking", &sheet_names);
+        let worksheet = workbook.add_worksheet().set_name(&name)?;
+        write_parking_sheet(worksheet, parking)?;
+    }
+
+    // Convert workbook to bytes
+    let xlsx_data = workbook.save_to_buffer()?;
+
+    Ok((filename, xlsx_data))
+}
+
+// Excel sheet names are at most 31 characters, cannot hold []:*?/\ and must be unique
+// regardless of case
+fn sheet_name(name: &str, taken: &[String]) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+        .take(28)
+        .collect();
+    let mut candidate = cleaned.clone();
+    let mut suffix = 2;
+    while taken.contains(&candidate.to_lowercase()) {
+        candidate = format!("{} {}", cleaned, suffix);
+        suffix += 1;
+    }
+    candidate
+}
 
+fn write_schedule_sheet(
+    worksheet: &mut Worksheet,
+    days: &[(NaiveDate, Vec<Employee>)],
+    seats: &DatedSeatPlan,
+) -> Result<(), XlsxError> {
     // Define formats
     let header_format = Format::new()
         .set_bold()
@@ -233,73 +290,90 @@ pub fn generate_xlsx_data(
 
     // Set column widths
     worksheet.set_column_width(0, 17.0)?; // Name column
-    for i in 1..=5 {
-        worksheet.set_column_width(i as u16, 12.0)?; // Weekday columns
+    for i in 1..=days.len() {
+        worksheet.set_column_width(i as u16, 12.0)?; // Date columns
     }
 
     // --- Header Row 1 ---
     worksheet.write_string_with_format(0, 0, "Name", &header_format)?;
-    for (i, day) in weekdays.iter().enumerate() {
-        worksheet.write_string_with_format(0, (i + 1) as u16, &day.to_string(), &header_format)?;
+    for (i, (date, _)) in days.iter().enumerate() {
+        worksheet.write_string_with_format(0, (i + 1) as u16, date_label(date), &header_format)?;
     }
 
     // --- Header Row 2 (Counts) ---
     worksheet.write_string_with_format(1, 0, "", &count_format)?; // Empty cell for name column
-    for (i, day) in weekdays.iter().enumerate() {
-        let count = schedule.get(day).map_or(0, |emps| emps.len());
+    for (i, (_, employees)) in days.iter().enumerate() {
         worksheet.write_string_with_format(
             1,
             (i + 1) as u16,
-            &format!("{}", count),
+            employees.len().to_string(),
             &count_format,
         )?;
     }
 
     // --- Data Rows ---
-    // 1. Collect all unique employees and sort them by name
-    let mut all_employee_refs: Vec<&Employee> = schedule
-        .values()
-        .flatten()
-        .collect::<HashSet<_>>()
-        .into_iter()
-        .collect();
-    all_employee_refs.sort_by(|a, b| a.name.cmp(&b.name));
-
-    // 2. Create assignment lookup
-    let assignments: HashMap<Weekday, HashSet<String>> = schedule
-        .iter()
-        .map(|(day, emps)| {
-            let names = emps.iter().map(|e| e.name.clone()).collect::<HashSet<_>>();
-            (day.clone(), names)
-        })
-        .collect();
-
-    // 3. Generate data rows
-    for (row_idx, emp) in all_employee_refs.iter().enumerate() {
+    for (row_idx, emp) in employees_in(days).iter().enumerate() {
         let excel_row = (row_idx + 2) as u32; // Start from row 2 (0-indexed, after headers)
 
         // Employee name (bold)
         worksheet.write_string_with_format(excel_row, 0, &emp.name, &name_format)?;
 
-        // Assignment status for each day
-        for (col_idx, day) in weekdays.iter().enumerate() {
+        // Assignment status for each date
+        for (col_idx, (date, employees)) in days.iter().enumerate() {
             let excel_col = (col_idx + 1) as u16;
-            let is_assigned = assignments
-                .get(day)
-                .map_or(false, |names_on_day| names_on_day.contains(&emp.name));
-
-            if is_assigned {
-                worksheet.write_string_with_format(excel_row, excel_col, "X", &x_format)?;
+            if employees.iter().any(|e| e.id == emp.id) {
+                let desk = seats.get(date).and_then(|day_seats| day_seats.get(&emp.id));
+                let cell = desk.map_or("X", |desk| desk.name.as_str());
+                worksheet.write_string_with_format(excel_row, excel_col, cell, &x_format)?;
             } else {
                 worksheet.write_string_with_format(excel_row, excel_col, "", &data_format)?;
             }
         }
     }
 
-    // Convert workbook to bytes
-    let xlsx_data = workbook.save_to_buffer()?;
+    Ok(())
+}
 
-    Ok((filename, xlsx_data))
+fn write_parking_sheet(worksheet: &mut Worksheet, parking: &ParkingPlan) -> Result<(), XlsxError> {
+    let header_format = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0x4F81BD))
+        .set_font_color(Color::White)
+        .set_align(FormatAlign::Center)
+        .set_border(FormatBorder::Thin)
+        .set_font_size(14);
+
+    let date_format = Format::new()
+        .set_bold()
+        .set_align(FormatAlign::Center)
+        .set_border(FormatBorder::Thin)
+        .set_font_size(11);
+
+    let data_format = Format::new().set_text_wrap().set_border(FormatBorder::Thin);
+
+    worksheet.set_column_width(0, 17.0)?;
+    worksheet.set_column_width(1, 50.0)?;
+    worksheet.set_column_width(2, 35.0)?;
+
+    for (col, title) in ["Date", "Parking", "No space"].iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *title, &header_format)?;
+    }
+
+    for (row_idx, (date, day)) in parking.iter().enumerate() {
+        let excel_row = (row_idx + 1) as u32;
+        let names = |employees: &[Employee]| {
+            employees
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        worksheet.write_string_with_format(excel_row, 0, date_label(date), &date_format)?;
+        worksheet.write_string_with_format(excel_row, 1, names(&day.parked), &data_format)?;
+        worksheet.write_string_with_format(excel_row, 2, names(&day.missed), &data_format)?;
+    }
+
+    Ok(())
 }
 
 // Alternative function that returns both CSV and XLSX
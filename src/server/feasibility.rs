@@ -0,0 +1,437 @@
+use crate::server::calendar::weekday_of;
+use crate::server::scheduler::{is_available, pair_violation};
+use crate::server::schema::{
+    Employee, FeasibilityIssue, PairKind, ScheduleConstraints, ScheduleGenerator, SchedulerConfig,
+    Weekday,
+};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+// Every set of office days the employee could end up with: their fixed or pinned days, or
+// each combination of their required size that keeps their pins and avoids unavailable days
+fn possible_days(generator: &ScheduleGenerator, employee: &Employee) -> Vec<Vec<Weekday>> {
+    if !employee.fixed_days.is_empty() {
+        return vec![employee.fixed_days.clone()];
+    }
+    let required = generator.required_days_for(employee);
+    let pinned = generator.pinned_days(employee.id);
+    if !pinned.is_empty() && pinned.len() >= required {
+        return vec![pinned.to_vec()];
+    }
+    generator
+        .combinations_with(required, pinned)
+        .iter()
+        .filter(|combo| is_available(&combo.days, employee))
+        .map(|combo| combo.days.clone())
+        .collect()
+}
+
+fn office_days(generator: &ScheduleGenerator, employee: &Employee) -> usize {
+    if employee.fixed_days.is_empty() {
+        generator.required_days_for(employee)
+    } else {
+        employee.fixed_days.len()
+    }
+}
+
+// Pins the schedule cannot keep: dates the person is unavailable, on leave or the office is
+// closed, and dates the pinned and fixed-day people alone fill beyond the day's capacity
+fn check_pins(
+    employees: &[Employee],
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    date_pins: &HashMap<usize, Vec<NaiveDate>>,
+    issues: &mut Vec<FeasibilityIssue>,
+) {
+    let closed = |date: &NaiveDate| constraints.closures.iter().any(|c| c.date == *date);
+    let on_leave = |employee: &Employee, date: &NaiveDate| {
+        constraints
+            .leave
+            .iter()
+            .any(|leave| leave.employee_id == employee.id && leave.covers(*date))
+    };
+
+    let mut pinned_by_date: BTreeMap<NaiveDate, Vec<&Employee>> = BTreeMap::new();
+    for employee in employees {
+        let Some(dates) = date_pins.get(&employee.id) else {
+            continue;
+        };
+        let mut dates = dates.clone();
+        dates.sort();
+        let unavailable: Vec<NaiveDate> = dates
+            .iter()
+            .filter(|date| employee.unavailable_days.contains(&weekday_of(**date)))
+            .copied()
+            .collect();
+        let leave: Vec<NaiveDate> = dates
+            .iter()
+            .filter(|date| on_leave(employee, date))
+            .copied()
+            .collect();
+        let closures: Vec<NaiveDate> = dates.iter().filter(|date| closed(date)).copied().collect();
+        if !unavailable.is_empty() {
+            issues.push(FeasibilityIssue::PinOnUnavailableDay {
+                employee: employee.name.clone(),
+                dates: unavailable,
+            });
+        }
+        if !leave.is_empty() {
+            issues.push(FeasibilityIssue::PinOnLeave {
+                employee: employee.name.clone(),
+                dates: leave,
+            });
+        }
+        if !closures.is_empty() {
+            issues.push(FeasibilityIssue::PinOnClosure {
+                employee: employee.name.clone(),
+                dates: closures,
+            });
+        }
+        for date in dates {
+            pinned_by_date.entry(date).or_default().push(employee);
+        }
+    }
+
+    for (date, pinned) in pinned_by_date {
+        let day = weekday_of(date);
+        let Some(capacity) = config.capacity_for(&day) else {
+            continue;
+        };
+        // Fixed days alone over capacity are reported as FixedDaysOverCapacity
+        let fixed = employees.iter().filter(|e| e.fixed_days.contains(&day));
+        if closed(&date) || fixed.count() > capacity {
+            continue;
+        }
+        let mut present: Vec<String> = employees
+            .iter()
+            .filter(|e| {
+                (e.fixed_days.contains(&day) || pinned.iter().any(|p| p.id == e.id))
+                    && !on_leave(e, &date)
+            })
+            .map(|e| e.name.clone())
+            .collect();
+        present.sort();
+        if present.len() > capacity {
+            issues.push(FeasibilityIssue::PinnedDayOverCapacity {
+                date,
+                capacity,
+                employees: present,
+            });
+        }
+    }
+}
+
+// Checks the inputs for problems that would otherwise only show up as a wrong schedule.
+// An empty list means generation can go ahead.
+pub fn check_feasibility(
+    employees: &[Employee],
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    date_pins: &HashMap<usize, Vec<NaiveDate>>,
+) -> Vec<FeasibilityIssue> {
+    let generator = ScheduleGenerator::new(config.clone(), constraints.clone());
+    let mut issues = Vec::new();
+
+    check_pins(employees, config, constraints, date_pins, &mut issues);
+
+    // Employees on their own
+    let mut without_combinations: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for employee in employees {
+        if !employee.fixed_days.is_empty() {
+            if employee.fixed_days.len() != employee.required_days as usize {
+                issues.push(FeasibilityIssue::FixedDaysMismatch {
+                    employee: employee.name.clone(),
+                    fixed: employee.fixed_days.len(),
+                    required: employee.required_days as usize,
+                });
+            }
+            let clashes: Vec<Weekday> = generator
+                .weekdays
+                .iter()
+                .filter(|day| {
+                    employee.fixed_days.contains(day) && employee.unavailable_days.contains(day)
+                })
+                .cloned()
+                .collect();
+            if !clashes.is_empty() {
+                issues.push(FeasibilityIssue::FixedOnUnavailableDay {
+                    employee: employee.name.clone(),
+                    days: clashes,
+                });
+            }
+            continue;
+        }
+
+        let required = generator.required_days_for(employee);
+        if generator
+            .day_combinations
+            .get(&required)
+            .is_none_or(|combos| combos.is_empty())
+        {
+            without_combinations
+                .entry(required)
+                .or_default()
+                .push(employee.name.clone());
+        } else if possible_days(&generator, employee).is_empty() {
+            issues.push(FeasibilityIssue::NoAvailableCombination {
+                employee: employee.name.clone(),
+                required_days: required,
+            });
+        }
+    }
+    for (required_days, employees) in without_combinations {
+        issues.push(FeasibilityIssue::NoDayCombinations {
+            required_days,
+            employees,
+        });
+    }
+
+    // Fixed days are never moved, so a day they overfill cannot be fixed by the scheduler
+    for day in &generator.weekdays {
+        if let Some(capacity) = config.capacity_for(day) {
+            let fixed: Vec<String> = employees
+                .iter()
+                .filter(|e| e.fixed_days.contains(day))
+                .map(|e| e.name.clone())
+                .collect();
+            if fixed.len() > capacity {
+                issues.push(FeasibilityIssue::FixedDaysOverCapacity {
+                    day: day.clone(),
+                    capacity,
+                    employees: fixed,
+                });
+            }
+        }
+    }
+
+    // Minimums that more people would need to hold the role than ever come in
+    for rule in &constraints.coverage_rules {
+        let Some(min) = rule.min_count else {
+            continue;
+        };
+        let holders: Vec<String> = employees
+            .iter()
+            .filter(|e| e.role == rule.role && office_days(&generator, e) > 0)
+            .map(|e| e.name.clone())
+            .collect();
+        if holders.len() < min {
+            issues.push(FeasibilityIssue::CoverageRuleUnreachable {
+                rule: rule.to_string(),
+                employees: holders,
+            });
+        }
+    }
+
+    if let Some(max_per_day) = config.nsp_policy.max_per_day {
+        let nsps: Vec<&Employee> = employees.iter().filter(|e| e.is_nsp).collect();
+        let needed = nsps
+            .iter()
+            .map(|e| office_days(&generator, e))
+            .sum::<usize>();
+        if needed > max_per_day * generator.weekdays.len() {
+            issues.push(FeasibilityIssue::NspCapTooLow {
+                max_per_day,
+                office_days: needed,
+                employees: nsps.iter().map(|e| e.name.clone()).collect(),
+            });
+        }
+    }
+
+    // Offices whose own staff need more desk-days than the office has in a week
+    for location in &constraints.locations {
+        let Some(capacity) = location.capacity else {
+            continue;
+        };
+        let staff: Vec<&Employee> = employees
+            .iter()
+            .filter(|e| e.office_location() == Some(location.id))
+            .collect();
+        let needed = staff
+            .iter()
+            .map(|e| office_days(&generator, e))
+            .sum::<usize>();
+        if needed > capacity * generator.weekdays.len() {
+            issues.push(FeasibilityIssue::LocationCapacityTooLow {
+                location: location.name.clone(),
+                capacity,
+                office_days: needed,
+                employees: staff.iter().map(|e| e.name.clone()).collect(),
+            });
+        }
+    }
+
+    // Pair rules that contradict each other or that no pair of day sets can meet
+    for (index, pair) in constraints.pairs.iter().enumerate() {
+        let conflicting = constraints.pairs[index + 1..].iter().any(|other| {
+            other.involves(pair.first_id)
+                && other.involves(pair.second_id)
+                && matches!(pair.kind, PairKind::Apart) != matches!(other.kind, PairKind::Apart)
+        });
+        if conflicting {
+            issues.push(FeasibilityIssue::ConflictingPairRules {
+                first: pair.first_name.clone(),
+                second: pair.second_name.clone(),
+            });
+            continue;
+        }
+
+        let (Some(first), Some(second)) = (
+            employees.iter().find(|e| e.id == pair.first_id),
+            employees.iter().find(|e| e.id == pair.second_id),
+        ) else {
+            continue;
+        };
+        let first_options = possible_days(&generator, first);
+        let second_options = possible_days(&generator, second);
+        if first_options.is_empty() || second_options.is_empty() {
+            // Already reported for the employee
+            continue;
+        }
+        let satisfiable = first_options.iter().any(|a| {
+            let a: Vec<&Weekday> = a.iter().collect();
+            second_options.iter().any(|b| {
+                let b: Vec<&Weekday> = b.iter().collect();
+                pair_violation(pair, &a, &b) == 0
+            })
+        });
+        if !satisfiable {
+            issues.push(FeasibilityIssue::PairUnsatisfiable {
+                rule: pair.to_string(),
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::schema::{Closure, Leave, LeaveKind};
+    use crate::server::test_support::employee;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 11, day).unwrap()
+    }
+
+    fn pins(entries: &[(usize, NaiveDate)]) -> HashMap<usize, Vec<NaiveDate>> {
+        let mut pins: HashMap<usize, Vec<NaiveDate>> = HashMap::new();
+        for (id, date) in entries {
+            pins.entry(*id).or_default().push(*date);
+        }
+        pins
+    }
+
+    #[test]
+    fn pins_on_open_available_days_pass() {
+        let employees = vec![employee(1, 2), employee(2, 2)];
+        let issues = check_feasibility(
+            &employees,
+            &SchedulerConfig::default(),
+            &ScheduleConstraints::default(),
+            &pins(&[(1, date(2)), (2, date(3))]),
+        );
+        assert_eq!(issues, Vec::new());
+    }
+
+    #[test]
+    fn pin_on_an_unavailable_day_is_reported() {
+        let mut ama = employee(1, 2);
+        ama.unavailable_days = vec![Weekday::Monday];
+        let issues = check_feasibility(
+            &[ama],
+            &SchedulerConfig::default(),
+            &ScheduleConstraints::default(),
+            &pins(&[(1, date(2)), (1, date(4))]),
+        );
+        assert_eq!(
+            issues,
+            vec![FeasibilityIssue::PinOnUnavailableDay {
+                employee: "Employee 1".to_string(),
+                dates: vec![date(2)],
+            }]
+        );
+    }
+
+    #[test]
+    fn pin_on_leave_is_reported() {
+        let constraints = ScheduleConstraints {
+            leave: vec![Leave {
+                id: 1,
+                employee_id: 1,
+                kind: LeaveKind::Sick,
+                start: date(9),
+                end: date(11),
+            }],
+            ..Default::default()
+        };
+        let issues = check_feasibility(
+            &[employee(1, 2)],
+            &SchedulerConfig::default(),
+            &constraints,
+            &pins(&[(1, date(10)), (1, date(12))]),
+        );
+        assert_eq!(
+            issues,
+            vec![FeasibilityIssue::PinOnLeave {
+                employee: "Employee 1".to_string(),
+                dates: vec![date(10)],
+            }]
+        );
+    }
+
+    #[test]
+    fn pin_on_a_closure_is_reported() {
+        let constraints = ScheduleConstraints {
+            closures: vec![Closure {
+                id: 1,
+                date: date(4),
+                name: "Closed".to_string(),
+            }],
+            ..Default::default()
+        };
+        let issues = check_feasibility(
+            &[employee(1, 2)],
+            &SchedulerConfig::default(),
+            &constraints,
+            &pins(&[(1, date(4))]),
+        );
+        assert_eq!(
+            issues,
+            vec![FeasibilityIssue::PinOnClosure {
+                employee: "Employee 1".to_string(),
+                dates: vec![date(4)],
+            }]
+        );
+    }
+
+    #[test]
+    fn pinned_day_over_capacity_counts_fixed_days_too() {
+        let mut fixed = employee(3, 1);
+        fixed.fixed_days = vec![Weekday::Wednesday];
+        let employees = vec![employee(1, 2), employee(2, 2), fixed];
+        let config = SchedulerConfig {
+            default_capacity: Some(2),
+            ..Default::default()
+        };
+
+        let issues = check_feasibility(
+            &employees,
+            &config,
+            &ScheduleConstraints::default(),
+            &pins(&[(1, date(4)), (2, date(4)), (1, date(5))]),
+        );
+        assert_eq!(
+            issues,
+            vec![FeasibilityIssue::PinnedDayOverCapacity {
+                date: date(4),
+                capacity: 2,
+                employees: vec![
+                    "Employee 1".to_string(),
+                    "Employee 2".to_string(),
+                    "Employee 3".to_string(),
+                ],
+            }]
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use crate::server::{db, schema::Employee};
+use crate::server::{
+    db,
+    schema::{Closure, Employee},
+};
 use anyhow::Result;
-use dioxus::logger::tracing::{error, info};
+use chrono::NaiveDate;
+use dioxus::logger::tracing::{error, info, warn};
 use rusqlite::Connection;
 use serde::Deserialize;
 use std::error::Error;
@@ -14,6 +18,32 @@ pub struct EmployeeImport {
     pub required_days: u8,
     pub fixed_days: Vec<String>,
     pub is_nsp: bool,
+    #[serde(default)]
+    pub preferred_days: Vec<String>,
+    #[serde(default)]
+    pub unavailable_days: Vec<String>,
+    #[serde(default)]
+    pub needs_parking: bool,
+}
+
+fn parse_weekdays(
+    days: Vec<String>,
+) -> Result<Vec<crate::server::schema::Weekday>, Box<dyn Error>> {
+    let mut weekdays = Vec::new();
+    for day in days {
+        let weekday = match day.to_lowercase().as_str() {
+            "monday" => crate::server::schema::Weekday::Monday,
+            "tuesday" => crate::server::schema::Weekday::Tuesday,
+            "wednesday" => crate::server::schema::Weekday::Wednesday,
+            "thursday" => crate::server::schema::Weekday::Thursday,
+            "friday" => crate::server::schema::Weekday::Friday,
+            "saturday" => crate::server::schema::Weekday::Saturday,
+            "sunday" => crate::server::schema::Weekday::Sunday,
+            _ => return Err(format!("Invalid weekday value: {}", day).into()),
+        };
+        weekdays.push(weekday);
+    }
+    Ok(weekdays)
 }
 
 // Convert the imported data to the Employee struct
@@ -48,19 +78,10 @@ fn convert_to_employee(import: EmployeeImport) -> Result<Employee, Box<dyn Error
         _ => return Err(format!("Invalid role value: {}", import.role).into()),
     };
 
-    // Parse fixed days
-    let mut fixed_days = Vec::new();
-    for day in import.fixed_days {
-        let weekday = match day.to_lowercase().as_str() {
-            "monday" => crate::server::schema::Weekday::Monday,
-            "tuesday" => crate::server::schema::Weekday::Tuesday,
-            "wednesday" => crate::server::schema::Weekday::Wednesday,
-            "thursday" => crate::server::schema::Weekday::Thursday,
-            "friday" => crate::server::schema::Weekday::Friday,
-            _ => return Err(format!("Invalid weekday value: {}", day).into()),
-        };
-        fixed_days.push(weekday);
-    }
+    // Parse fixed, preferred and unavailable days
+    let fixed_days = parse_weekdays(import.fixed_days)?;
+    let preferred_days = parse_weekdays(import.preferred_days)?;
+    let unavailable_days = parse_weekdays(import.unavailable_days)?;
 
     Ok(Employee {
         id: 0, // added 0
@@ -70,6 +91,13 @@ fn convert_to_employee(import: EmployeeImport) -> Result<Employee, Box<dyn Error
         required_days: import.required_days,
         fixed_days,
         is_nsp: import.is_nsp,
+        preferred_days,
+        unavailable_days,
+        team_id: None,
+        location_id: None,
+        attends_location_id: None,
+        desk_needs: Vec::new(),
+        needs_parking: import.needs_parking,
     })
 }
 
@@ -117,3 +145,189 @@ pub fn save_imported_employees(conn: &Connection, employees: Vec<Employee>) -> R
 
     Ok(count)
 }
+
+// Date from an iCalendar DTSTART/DTEND value, either `20261225` or `20261225T090000Z`
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    let date = value.get(..8)?;
+    NaiveDate::parse_from_str(date, "%Y%m%d").ok()
+}
+
+fn unescape_ics_text(text: &str) -> String {
+    text.replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+// Reads every event of an .ics file as closures, one per day it covers. All-day events end
+// the day before DTEND; events without an end, or timed ones, close the day they start.
+// Events without a usable start date are skipped.
+pub fn import_closures_from_ics(ics_data: &str) -> Result<Vec<Closure>> {
+    // Long lines are folded onto following lines that start with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics_data.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut closures = Vec::new();
+    let mut in_event = false;
+    let mut start: Option<NaiveDate> = None;
+    let mut end: Option<(NaiveDate, bool)> = None;
+    let mut summary = String::new();
+
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as `;VALUE=DATE` follow the property name
+        let (name, params) = key.split_once(';').unwrap_or((key, ""));
+        match name.to_uppercase().as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = true;
+                start = None;
+                end = None;
+                summary.clear();
+            }
+            "DTSTART" if in_event => start = parse_ics_date(value),
+            "DTEND" if in_event => {
+                let all_day = params.to_uppercase().contains("VALUE=DATE") || value.len() == 8;
+                end = parse_ics_date(value).map(|date| (date, all_day));
+            }
+            "SUMMARY" if in_event => summary = unescape_ics_text(value),
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = false;
+                let Some(start) = start else {
+                    warn!("Skipped event '{}': it has no valid start date", summary);
+                    continue;
+                };
+                let last = match end {
+                    Some((end, true)) if end > start => end.pred_opt().unwrap_or(start),
+                    Some((end, false)) if end > start => end,
+                    _ => start,
+                };
+                let name = if summary.trim().is_empty() {
+                    "Closed".to_string()
+                } else {
+                    summary.trim().to_string()
+                };
+                for date in start.iter_days().take_while(|date| *date <= last) {
+                    closures.push(Closure {
+                        id: 0,
+                        date,
+                        name: name.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if closures.is_empty() {
+        return Err(anyhow::anyhow!("No events found in the calendar file"));
+    }
+    Ok(closures)
+}
+
+// Save imported closures to database, renaming dates that are already closed
+pub fn save_imported_closures(conn: &Connection, closures: Vec<Closure>) -> Result<usize> {
+    let mut count = 0;
+
+    for closure in closures {
+        match db::insert_closure(conn, &closure) {
+            Ok(_) => count += 1,
+            Err(e) => {
+                error!("Failed to import closure {}: {}", closure, e);
+                return Err(anyhow::anyhow!("Database error: {}", e));
+            }
+        }
+    }
+
+    info!("Imported {} closures", count);
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn dates(closures: &[Closure]) -> Vec<NaiveDate> {
+        closures.iter().map(|closure| closure.date).collect()
+    }
+
+    #[test]
+    fn all_day_event_ends_the_day_before_dtend() {
+        let ics = "BEGIN:VCALENDAR\n\
+                   BEGIN:VEVENT\n\
+                   DTSTART;VALUE=DATE:20261224\n\
+                   DTEND;VALUE=DATE:20261227\n\
+                   SUMMARY:Christmas\n\
+                   END:VEVENT\n\
+                   END:VCALENDAR\n";
+        let closures = import_closures_from_ics(ics).unwrap();
+        assert_eq!(
+            dates(&closures),
+            vec![date(2026, 12, 24), date(2026, 12, 25), date(2026, 12, 26)]
+        );
+        assert!(closures.iter().all(|closure| closure.name == "Christmas"));
+    }
+
+    #[test]
+    fn timed_event_closes_the_day_it_starts() {
+        let ics = "BEGIN:VCALENDAR\n\
+                   BEGIN:VEVENT\n\
+                   DTSTART;TZID=Africa/Accra:20260306T090000\n\
+                   DTEND;TZID=Africa/Accra:20260306T170000\n\
+                   SUMMARY:Independence Day\n\
+                   END:VEVENT\n\
+                   END:VCALENDAR\n";
+        let closures = import_closures_from_ics(ics).unwrap();
+        assert_eq!(dates(&closures), vec![date(2026, 3, 6)]);
+    }
+
+    #[test]
+    fn folded_summary_is_joined() {
+        let ics = "BEGIN:VCALENDAR\n\
+                   BEGIN:VEVENT\n\
+                   DTSTART;VALUE=DATE:20260501\n\
+                   SUMMARY:Workers' Day\\, office\n  closed\n\
+                   END:VEVENT\n\
+                   END:VCALENDAR\n";
+        let closures = import_closures_from_ics(ics).unwrap();
+        assert_eq!(closures.len(), 1);
+        assert_eq!(closures[0].name, "Workers' Day, office closed");
+    }
+
+    #[test]
+    fn event_without_start_is_skipped() {
+        let ics = "BEGIN:VCALENDAR\n\
+                   BEGIN:VEVENT\n\
+                   SUMMARY:No date\n\
+                   END:VEVENT\n\
+                   BEGIN:VEVENT\n\
+                   DTSTART;VALUE=DATE:20260101\n\
+                   SUMMARY:New Year\n\
+                   END:VEVENT\n\
+                   END:VCALENDAR\n";
+        let closures = import_closures_from_ics(ics).unwrap();
+        assert_eq!(dates(&closures), vec![date(2026, 1, 1)]);
+        assert_eq!(closures[0].name, "New Year");
+    }
+
+    #[test]
+    fn crlf_line_endings_are_read() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260703\r\n\
+                   DTEND;VALUE=DATE:20260704\r\nSUMMARY:Republic\r\n  Day\r\n\
+                   END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let closures = import_closures_from_ics(ics).unwrap();
+        assert_eq!(dates(&closures), vec![date(2026, 7, 3)]);
+        assert_eq!(closures[0].name, "Republic Day");
+    }
+}
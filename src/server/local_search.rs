@@ -0,0 +1,323 @@
+use crate::server::scheduler::{is_available, location_count, schedule_objective};
+use crate::server::schema::{Employee, MonthlySchedule, PastSchedules, ScheduleGenerator, Weekday};
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng};
+
+// Days the employee currently holds, in week order
+fn assigned_days(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    employee_id: usize,
+) -> Vec<Weekday> {
+    generator
+        .weekdays
+        .iter()
+        .filter(|day| {
+            schedule
+                .get(day)
+                .is_some_and(|emps| emps.iter().any(|e| e.id == employee_id))
+        })
+        .cloned()
+        .collect()
+}
+
+// Days locked on the Schedules page must survive a move
+fn keeps_pins(generator: &ScheduleGenerator, employee: &Employee, days: &[Weekday]) -> bool {
+    generator
+        .pinned_days(employee.id)
+        .iter()
+        .all(|day| days.contains(day))
+}
+
+fn move_employee(
+    schedule: &mut MonthlySchedule,
+    employee: &Employee,
+    from: &[Weekday],
+    to: &[Weekday],
+) {
+    for day in from {
+        if let Some(daily_schedule) = schedule.get_mut(day) {
+            daily_schedule.retain(|e| e.id != employee.id);
+        }
+    }
+    for day in to {
+        if let Some(daily_schedule) = schedule.get_mut(day) {
+            daily_schedule.push(employee.clone());
+        }
+    }
+}
+
+// Capacity, location capacity and NSP cap still hold on the given days after a move
+fn within_limits(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    days: &[Weekday],
+) -> bool {
+    let nsp_cap = generator.config.nsp_policy.max_per_day;
+    days.iter().all(|day| {
+        let emps = schedule.get(day).map_or(&[][..], |emps| emps.as_slice());
+        generator
+            .config
+            .capacity_for(day)
+            .is_none_or(|cap| emps.len() <= cap)
+            && nsp_cap.is_none_or(|cap| emps.iter().filter(|e| e.is_nsp).count() <= cap)
+            && generator.constraints.locations.iter().all(|location| {
+                location
+                    .capacity
+                    .is_none_or(|cap| location_count(schedule, day, location.id) <= cap)
+            })
+    })
+}
+
+// Hill-climbing pass over a finished schedule. Each iteration either moves one flexible
+// employee to another combination or swaps the days of two employees with the same
+// number of office days, and keeps the change only if `schedule_objective` goes down.
+// Fixed-day employees are never passed in, so they never move.
+pub fn refine_schedule(
+    generator: &ScheduleGenerator,
+    schedule: &mut MonthlySchedule,
+    flexible_employees: &[Employee],
+    past_schedules: &PastSchedules,
+    female_ratio: f64,
+    rng: &mut StdRng,
+) {
+    let iterations = generator.config.local_search_iterations;
+    // Employees left out for lack of capacity stay out
+    let movable: Vec<&Employee> = flexible_employees
+        .iter()
+        .filter(|e| !assigned_days(generator, schedule, e.id).is_empty())
+        .collect();
+    if iterations == 0 || movable.is_empty() {
+        return;
+    }
+
+    let mut current_score = schedule_objective(generator, schedule, past_schedules, female_ratio);
+
+    for _ in 0..iterations {
+        let Some(&employee) = movable.choose(rng) else {
+            break;
+        };
+        let old_days = assigned_days(generator, schedule, employee.id);
+
+        if movable.len() > 1 && rng.random_bool(0.5) {
+            // Swap the combinations of two employees; daily headcounts stay the same
+            let Some(&partner) = movable.choose(rng) else {
+                continue;
+            };
+            let partner_days = assigned_days(generator, schedule, partner.id);
+            if partner.id == employee.id
+                || partner_days.len() != old_days.len()
+                || partner_days == old_days
+                || !is_available(&partner_days, employee)
+                || !is_available(&old_days, partner)
+                || !keeps_pins(generator, employee, &partner_days)
+                || !keeps_pins(generator, partner, &old_days)
+            {
+                continue;
+            }
+
+            move_employee(schedule, employee, &old_days, &partner_days);
+            move_employee(schedule, partner, &partner_days, &old_days);
+            if within_limits(generator, schedule, &old_days)
+                && within_limits(generator, schedule, &partner_days)
+            {
+                let score = schedule_objective(generator, schedule, past_schedules, female_ratio);
+                if score < current_score {
+                    current_score = score;
+                    continue;
+                }
+            }
+            move_employee(schedule, partner, &old_days, &partner_days);
+            move_employee(schedule, employee, &partner_days, &old_days);
+        } else {
+            // Move one employee to another combination of the same size
+            let Some(combo) = generator
+                .day_combinations
+                .get(&old_days.len())
+                .and_then(|combos| combos.choose(rng))
+            else {
+                continue;
+            };
+            let new_days: Vec<Weekday> = generator
+                .weekdays
+                .iter()
+                .filter(|day| combo.days.contains(day))
+                .cloned()
+                .collect();
+            if new_days == old_days
+                || !is_available(&new_days, employee)
+                || !keeps_pins(generator, employee, &new_days)
+            {
+                continue;
+            }
+
+            move_employee(schedule, employee, &old_days, &new_days);
+            if within_limits(generator, schedule, &new_days) {
+                let score = schedule_objective(generator, schedule, past_schedules, female_ratio);
+                if score < current_score {
+                    current_score = score;
+                    continue;
+                }
+            }
+            move_employee(schedule, employee, &new_days, &old_days);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::scheduler::company_female_ratio;
+    use crate::server::schema::{NspPolicy, ScheduleConstraints, SchedulerConfig};
+    use crate::server::test_support::employee;
+    use rand::SeedableRng;
+    use std::collections::{HashMap, HashSet};
+
+    const FIXED: usize = 1;
+    const PINNED: usize = 2;
+
+    // Eight people on two days each. Employee 1 has fixed days, employee 2 has Monday pinned
+    // and employees 3 and 4 are NSPs, at most one of them in on any day.
+    fn generator() -> ScheduleGenerator {
+        let config = SchedulerConfig {
+            default_capacity: Some(4),
+            nsp_policy: NspPolicy {
+                max_per_day: Some(1),
+                ..Default::default()
+            },
+            local_search_iterations: 2000,
+            ..Default::default()
+        };
+        let constraints = ScheduleConstraints {
+            pins: HashMap::from([(PINNED, vec![Weekday::Monday])]),
+            ..Default::default()
+        };
+        ScheduleGenerator::new(config, constraints)
+    }
+
+    fn employees() -> Vec<Employee> {
+        let mut employees: Vec<Employee> = (1..=8).map(|id| employee(id, 2)).collect();
+        employees[0].fixed_days = vec![Weekday::Monday, Weekday::Tuesday];
+        employees[2].is_nsp = true;
+        employees[3].is_nsp = true;
+        employees
+    }
+
+    // A valid start that repeats last month's days for everyone and leaves Friday nearly empty,
+    // so the moves worth making are the ones the limits have to stop
+    fn starting_schedule(employees: &[Employee]) -> (MonthlySchedule, PastSchedules) {
+        let days: [(Weekday, &[usize]); 5] = [
+            (Weekday::Monday, &[1, 2, 3, 5]),
+            (Weekday::Tuesday, &[1, 2, 4, 6]),
+            (Weekday::Wednesday, &[5, 6, 7, 8]),
+            (Weekday::Thursday, &[3, 7, 8]),
+            (Weekday::Friday, &[4]),
+        ];
+        let mut schedule = MonthlySchedule::new();
+        let mut past = PastSchedules::new();
+        for (day, ids) in days {
+            for id in ids {
+                past.entry(*id)
+                    .or_insert_with(|| vec![HashSet::new()])
+                    .first_mut()
+                    .unwrap()
+                    .insert(day.clone());
+            }
+            let present = employees
+                .iter()
+                .filter(|e| ids.contains(&e.id))
+                .cloned()
+                .collect();
+            schedule.insert(day, present);
+        }
+        (schedule, past)
+    }
+
+    fn refined(
+        seed: u64,
+    ) -> (
+        ScheduleGenerator,
+        MonthlySchedule,
+        MonthlySchedule,
+        PastSchedules,
+    ) {
+        let generator = generator();
+        let employees = employees();
+        let (start, past) = starting_schedule(&employees);
+        let flexible: Vec<Employee> = employees
+            .iter()
+            .filter(|e| e.fixed_days.is_empty())
+            .cloned()
+            .collect();
+        let mut schedule = start.clone();
+        refine_schedule(
+            &generator,
+            &mut schedule,
+            &flexible,
+            &past,
+            company_female_ratio(&employees),
+            &mut StdRng::seed_from_u64(seed),
+        );
+        (generator, start, schedule, past)
+    }
+
+    fn days_by_employee(
+        generator: &ScheduleGenerator,
+        schedule: &MonthlySchedule,
+    ) -> Vec<Vec<Weekday>> {
+        employees()
+            .iter()
+            .map(|e| assigned_days(generator, schedule, e.id))
+            .collect()
+    }
+
+    #[test]
+    fn fixed_and_pinned_days_never_move() {
+        for seed in 0..10 {
+            let (generator, start, schedule, _) = refined(seed);
+            assert_ne!(
+                days_by_employee(&generator, &schedule),
+                days_by_employee(&generator, &start),
+                "seed {}",
+                seed
+            );
+
+            assert_eq!(
+                assigned_days(&generator, &schedule, FIXED),
+                vec![Weekday::Monday, Weekday::Tuesday],
+                "seed {}",
+                seed
+            );
+            let employees = employees();
+            let pinned = &employees[PINNED - 1];
+            let days = assigned_days(&generator, &schedule, PINNED);
+            assert!(keeps_pins(&generator, pinned, &days), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn refined_schedule_stays_within_limits() {
+        for seed in 0..10 {
+            let (generator, _, schedule, _) = refined(seed);
+            assert!(
+                within_limits(&generator, &schedule, &generator.weekdays),
+                "seed {}",
+                seed
+            );
+            for employee in employees() {
+                let days = assigned_days(&generator, &schedule, employee.id);
+                assert_eq!(days.len(), 2, "seed {} employee {}", seed, employee.id);
+            }
+        }
+    }
+
+    #[test]
+    fn refinement_never_raises_the_objective() {
+        let female_ratio = company_female_ratio(&employees());
+        for seed in 0..10 {
+            let (generator, start, schedule, past) = refined(seed);
+            let before = schedule_objective(&generator, &start, &past, female_ratio);
+            let after = schedule_objective(&generator, &schedule, &past, female_ratio);
+            assert!(after <= before, "seed {}: {} > {}", seed, after, before);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::server::schema::{
+    Booking, BookingWarning, DatedSchedule, Employee, EmployeeId, MeetingDay, MeetingRoom,
+};
+use std::cmp::Reverse;
+
+// Rooms not tied to an office, and staff without one, always match
+fn at_room_office(employee: &Employee, room: &MeetingRoom) -> bool {
+    room.location_id.is_none()
+        || employee.office_location().is_none()
+        || employee.office_location() == room.location_id
+}
+
+fn invitees<'a>(employees: &'a [Employee], attendees: &[EmployeeId]) -> Vec<&'a Employee> {
+    attendees
+        .iter()
+        .filter_map(|id| employees.iter().find(|e| e.id == *id))
+        .collect()
+}
+
+// Checks a month's bookings against who is in the office on each date of that month.
+// Attendees who are no longer on file are skipped.
+pub fn booking_warnings(
+    bookings: &[Booking],
+    rooms: &[MeetingRoom],
+    employees: &[Employee],
+    dated: &DatedSchedule,
+) -> Vec<BookingWarning> {
+    let mut warnings = Vec::new();
+    for (index, booking) in bookings.iter().enumerate() {
+        let Some(room) = rooms.iter().find(|room| room.id == booking.room_id) else {
+            continue;
+        };
+        let label = booking.to_string();
+
+        if booking.attendees.len() > room.capacity {
+            warnings.push(BookingWarning::RoomOverCapacity {
+                booking: label.clone(),
+                room: room.name.clone(),
+                attendees: booking.attendees.len(),
+                capacity: room.capacity,
+            });
+        }
+        for other in &bookings[index + 1..] {
+            if booking.clashes_with(other) {
+                warnings.push(BookingWarning::RoomDoubleBooked {
+                    room: room.name.clone(),
+                    first: label.clone(),
+                    second: other.to_string(),
+                });
+            }
+        }
+
+        // Weekends and closures have nobody scheduled
+        let Some(present) = dated.get(&booking.date).filter(|ids| !ids.is_empty()) else {
+            warnings.push(BookingWarning::NoOfficeDay {
+                booking: label,
+                date: booking.date,
+            });
+            continue;
+        };
+        for employee in invitees(employees, &booking.attendees) {
+            if !present.contains(&employee.id) {
+                warnings.push(BookingWarning::AttendeeNotInOffice {
+                    booking: label.clone(),
+                    employee: employee.name.clone(),
+                    date: booking.date,
+                });
+            } else if !at_room_office(employee, room) {
+                warnings.push(BookingWarning::AttendeeAtOtherOffice {
+                    booking: label.clone(),
+                    employee: employee.name.clone(),
+                    room: room.name.clone(),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+// Office dates of the month ranked by how many invitees are in, earliest first on a tie.
+// With a room given, people working from another office count as missing.
+pub fn suggest_meeting_days(
+    dated: &DatedSchedule,
+    employees: &[Employee],
+    attendees: &[EmployeeId],
+    room: Option<&MeetingRoom>,
+) -> Vec<MeetingDay> {
+    let invited = invitees(employees, attendees);
+    if invited.is_empty() {
+        return Vec::new();
+    }
+
+    let mut days: Vec<MeetingDay> = dated
+        .iter()
+        .filter(|(_, present)| !present.is_empty())
+        .map(|(date, present)| {
+            let missing: Vec<String> = invited
+                .iter()
+                .filter(|employee| {
+                    !present.contains(&employee.id)
+                        || room.is_some_and(|room| !at_room_office(employee, room))
+                })
+                .map(|employee| employee.name.clone())
+                .collect();
+            MeetingDay {
+                date: *date,
+                present: invited.len() - missing.len(),
+                missing,
+            }
+        })
+        .collect();
+    days.sort_by_key(|day| (Reverse(day.present), day.date));
+    days
+}
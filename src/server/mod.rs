@@ -1,5 +1,14 @@
+pub mod calendar;
 pub mod db;
 pub mod export;
+pub mod feasibility;
 pub mod import;
+pub mod local_search;
+pub mod meetings;
+pub mod parking;
 pub mod scheduler;
 pub mod schema;
+pub mod seating;
+pub mod solver;
+#[cfg(test)]
+mod test_support;
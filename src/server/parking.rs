@@ -0,0 +1,60 @@
+use crate::server::calendar::weekday_of;
+use crate::server::schema::{
+    Employee, ParkingDay, ParkingLedger, ParkingPlan, ParkingTally, ScheduleWeek,
+};
+use chrono::Datelike;
+use std::cmp::Ordering;
+
+// Lower share of past requests granted comes first. Compared by cross-multiplying; the +1
+// keeps someone with no history from dividing by zero.
+fn share_order(a: &ParkingTally, b: &ParkingTally) -> Ordering {
+    (a.parked * (b.requested + 1)).cmp(&(b.parked * (a.requested + 1)))
+}
+
+// Hands out the parking spaces on each office date of the month, in date order. When more
+// people need a space than there are spaces, those who have been given the smallest share of
+// the days they asked for, in `history` and earlier in this month, park first. Ties rotate
+// day by day so the same person does not always lose them.
+pub fn allocate_parking(
+    weeks: &[ScheduleWeek],
+    spaces: usize,
+    history: &ParkingLedger,
+) -> ParkingPlan {
+    let mut tallies = history.clone();
+    let mut plan = ParkingPlan::new();
+    for week in weeks {
+        for date in &week.dates {
+            let Some(present) = week.result.schedule.get(&weekday_of(*date)) else {
+                continue;
+            };
+            let mut drivers: Vec<&Employee> = present.iter().filter(|e| e.needs_parking).collect();
+            drivers.sort_by_key(|employee| employee.id);
+            if !drivers.is_empty() {
+                let offset = date.ordinal0() as usize % drivers.len();
+                drivers.rotate_left(offset);
+            }
+            // Stable, so the rotation decides ties
+            drivers.sort_by(|a, b| {
+                let a = tallies.get(&a.id).copied().unwrap_or_default();
+                let b = tallies.get(&b.id).copied().unwrap_or_default();
+                share_order(&a, &b)
+            });
+
+            let mut day = ParkingDay::default();
+            for (index, employee) in drivers.into_iter().enumerate() {
+                let tally = tallies.entry(employee.id).or_default();
+                tally.requested += 1;
+                if index < spaces {
+                    tally.parked += 1;
+                    day.parked.push(employee.clone());
+                } else {
+                    day.missed.push(employee.clone());
+                }
+            }
+            day.parked.sort_by(|a, b| a.name.cmp(&b.name));
+            day.missed.sort_by(|a, b| a.name.cmp(&b.name));
+            plan.insert(*date, day);
+        }
+    }
+    plan
+}
@@ -1,15 +1,25 @@
+use crate::server::calendar::{month_weeks, restrict_to_dates, to_dated, weekday_of};
+use crate::server::local_search::refine_schedule;
 use crate::server::schema::{
-    DayCombination, DayCount, Employee, MonthlySchedule, PastSchedules, ScheduleGenerator, Weekday,
+    Closure, CombinationScore, CoverageRule, DayCombination, DayCount, Employee,
+    EmployeeExplanation, Leave, MonthlySchedule, NspPolicy, PairConstraint, PairKind,
+    PastSchedules, Placement, Role, ScheduleConstraints, ScheduleGenerator, ScheduleOption,
+    ScheduleResult, ScheduleWarning, ScheduleWeek, SchedulerConfig, Sex, SolverMode, Team, Weekday,
 }; // ScheduleStatistics
-use rand::{rng, seq::SliceRandom};
-use std::collections::HashMap;
+use crate::server::solver::{share_of_budget, solve_exact, solver_deadline};
+use chrono::NaiveDate;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Cost of each unmet coverage rule, NSP rule, role maximum breach or pair rule day
+const COVERAGE_WEIGHT: f64 = 10.0;
 
 pub fn generate_schedule(
     generator: &ScheduleGenerator,
     employees: &[Employee],
     past_schedules: &PastSchedules,
-) -> MonthlySchedule {
-    // let mut rng = rng();
+    rng: &mut StdRng,
+) -> ScheduleResult {
     let mut day_counts: DayCount = generator
         .weekdays
         .iter()
@@ -24,24 +34,106 @@ pub fn generate_schedule(
 
     // Process employees with fixed schedules first
     let (flexible_employees, fixed_employees) =
-        process_fixed_schedules(employees, &mut day_counts, &mut schedule);
+        process_fixed_schedules(generator, employees, &mut day_counts, &mut schedule);
+    let mut explanations: HashMap<usize, EmployeeExplanation> = fixed_employees
+        .iter()
+        .map(|employee| {
+            let placement = if employee.fixed_days.is_empty() {
+                Placement::Pinned
+            } else {
+                Placement::Fixed
+            };
+            (employee.id, explain(placement, Vec::new()))
+        })
+        .collect();
 
-    // Group flexible employees by required days
-    let grouped_employees = group_by_required_days(&flexible_employees);
+    // Fixed days are never moved, so a day they overfill can only be flagged
+    let mut warnings = Vec::new();
+    for day in &generator.weekdays {
+        if let Some(capacity) = generator.config.capacity_for(day) {
+            let count = day_counts.get(day).copied().unwrap_or(0);
+            if count > capacity {
+                warnings.push(ScheduleWarning::DayOverCapacity {
+                    day: day.clone(),
+                    count,
+                    capacity,
+                });
+            }
+        }
+        for location in &generator.constraints.locations {
+            let Some(capacity) = location.capacity else {
+                continue;
+            };
+            let count = location_count(&schedule, day, location.id);
+            if count > capacity {
+                warnings.push(ScheduleWarning::LocationOverCapacity {
+                    location: location.name.clone(),
+                    day: day.clone(),
+                    count,
+                    capacity,
+                });
+            }
+        }
+    }
+
+    // Company-wide share of women, the ratio each day is steered towards
+    let female_ratio = company_female_ratio(employees);
+
+    // NSPs who need a regular colleague alongside them go last, once those colleagues are placed
+    let nsp_policy = &generator.config.nsp_policy;
+    let passes: Vec<Vec<Employee>> = if nsp_policy.require_colleague_overlap {
+        let (nsps, regular): (Vec<Employee>, Vec<Employee>) =
+            flexible_employees.iter().cloned().partition(|e| e.is_nsp);
+        vec![regular, nsps]
+    } else {
+        vec![flexible_employees.clone()]
+    };
+
+    for pass_employees in passes {
+        // Group flexible employees by required days
+        let grouped_employees = group_by_required_days(generator, &pass_employees, rng);
+
+        // Process flexible employees (prioritize those with more required days)
+        process_flexible_employees(
+            generator,
+            grouped_employees,
+            &mut day_counts,
+            &mut schedule,
+            past_schedules,
+            female_ratio,
+            &mut warnings,
+            &mut explanations,
+            rng,
+        );
+    }
 
-    // Process flexible employees (prioritize those with more required days)
-    process_flexible_employees(
+    refine_schedule(
         generator,
-        grouped_employees,
-        &mut day_counts,
         &mut schedule,
+        &flexible_employees,
         past_schedules,
+        female_ratio,
+        rng,
     );
 
-    schedule
+    check_coverage_rules(generator, &schedule, &mut warnings);
+    check_nsp_policy(generator, &schedule, &mut warnings);
+    check_team_colocation(generator, &schedule, &mut warnings);
+    check_pair_constraints(generator, &schedule, &mut warnings);
+
+    ScheduleResult {
+        schedule,
+        warnings,
+        proven_optimal: None,
+        explanations,
+        ..Default::default()
+    }
 }
 
-fn process_fixed_schedules(
+// Employees pinned on at least as many days as they need are placed here as well;
+// anyone pinned on fewer stays flexible and keeps their pinned days
+pub fn process_fixed_schedules(
+    generator: &ScheduleGenerator,
     employees: &[Employee],
     day_counts: &mut DayCount,
     schedule: &mut MonthlySchedule,
@@ -50,9 +142,18 @@ fn process_fixed_schedules(
     let mut fixed_employees = Vec::new();
 
     for employee in employees {
-        if !employee.fixed_days.is_empty() {
+        let pinned = generator.pinned_days(employee.id);
+        let fully_pinned =
+            !pinned.is_empty() && pinned.len() >= generator.required_days_for(employee);
+        let days = if employee.fixed_days.is_empty() {
+            pinned
+        } else {
+            &employee.fixed_days
+        };
+
+        if !employee.fixed_days.is_empty() || fully_pinned {
             // This employee has fixed days
-            for day in &employee.fixed_days {
+            for day in days {
                 if let Some(daily_schedule) = schedule.get_mut(day) {
                     if !daily_schedule.iter().any(|e| e.id == employee.id) {
                         daily_schedule.push(employee.clone());
@@ -69,93 +170,727 @@ fn process_fixed_schedules(
     (flexible_employees, fixed_employees)
 }
 
-fn group_by_required_days(employees: &[Employee]) -> HashMap<usize, Vec<Employee>> {
+fn group_by_required_days(
+    generator: &ScheduleGenerator,
+    employees: &[Employee],
+    rng: &mut StdRng,
+) -> HashMap<usize, Vec<Employee>> {
     let mut grouped: HashMap<usize, Vec<Employee>> = HashMap::new();
 
     for employee in employees {
         grouped
-            .entry(employee.required_days as usize)
+            .entry(generator.required_days_for(employee))
             .or_insert_with(Vec::new)
             .push(employee.clone());
     }
 
-    // Shuffle each group for randomization
-    let mut rng = rng();
-    for (_days, group) in grouped.iter_mut() {
-        group.shuffle(&mut rng);
+    // Shuffle each group for randomization, in key order so a seed always gives the same result
+    let mut keys: Vec<usize> = grouped.keys().copied().collect();
+    keys.sort();
+    for days in keys {
+        if let Some(group) = grouped.get_mut(&days) {
+            group.shuffle(rng);
+        }
     }
 
     grouped
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_flexible_employees(
     generator: &ScheduleGenerator,
     grouped_employees: HashMap<usize, Vec<Employee>>,
     day_counts: &mut DayCount,
     schedule: &mut MonthlySchedule,
     past_schedules: &PastSchedules,
+    female_ratio: f64,
+    warnings: &mut Vec<ScheduleWarning>,
+    explanations: &mut HashMap<usize, EmployeeExplanation>,
+    rng: &mut StdRng,
 ) {
     // Sort keys by number of required days (higher first)
     let mut keys: Vec<usize> = grouped_employees.keys().cloned().collect();
     keys.sort_by(|a, b| b.cmp(a));
 
     for num_days in keys {
+        // Fully remote employees have no office days to assign
+        if num_days == 0 {
+            for employee in grouped_employees.get(&num_days).into_iter().flatten() {
+                explanations.insert(employee.id, explain(Placement::Remote, Vec::new()));
+            }
+            continue;
+        }
+
         if let Some(employees_list) = grouped_employees.get(&num_days) {
-            if let Some(available_combos) = generator.day_combinations.get(&num_days) {
+            if generator
+                .day_combinations
+                .get(&num_days)
+                .is_none_or(|combos| combos.is_empty())
+            {
+                // More required days than the work week has: report instead of dropping silently
                 for employee in employees_list {
-                    // Find best day combination
-                    let best_combo = find_best_day_combination(
-                        available_combos,
-                        day_counts,
-                        employee,
-                        past_schedules,
-                    );
-
-                    // Assign employee to days from the best combination
-                    for day in &best_combo.days {
-                        if let Some(daily_schedule) = schedule.get_mut(day) {
-                            if !daily_schedule.iter().any(|e| e.id == employee.id) {
-                                daily_schedule.push(employee.clone());
-                                *day_counts.entry(day.clone()).or_insert(0) += 1;
-                            }
+                    warnings.push(ScheduleWarning::UnplacedEmployee {
+                        employee: employee.clone(),
+                        days: num_days,
+                    });
+                    explanations.insert(employee.id, explain(Placement::Unplaced, Vec::new()));
+                }
+                continue;
+            }
+
+            for employee in employees_list {
+                // Pinned days narrow the combinations; unavailable days rule one out entirely
+                let available_combos =
+                    generator.combinations_with(num_days, generator.pinned_days(employee.id));
+                let open_combos: Vec<&DayCombination> = available_combos
+                    .iter()
+                    .filter(|combo| is_available(&combo.days, employee))
+                    .collect();
+                if open_combos.is_empty() {
+                    warnings.push(ScheduleWarning::NoAvailableCombination {
+                        employee: employee.clone(),
+                        days: num_days,
+                    });
+                    explanations.insert(employee.id, explain(Placement::Unplaced, Vec::new()));
+                    continue;
+                }
+
+                // Capacity is a hard limit: only combinations with room on every day qualify
+                let fitting_combos: Vec<DayCombination> = open_combos
+                    .into_iter()
+                    .filter(|combo| fits_capacity(combo, day_counts, &generator.config))
+                    .filter(|combo| fits_location_capacity(combo, employee, schedule, generator))
+                    .filter(|combo| {
+                        !employee.is_nsp
+                            || fits_nsp_cap(combo, schedule, &generator.config.nsp_policy)
+                    })
+                    .cloned()
+                    .collect();
+
+                if fitting_combos.is_empty() {
+                    warnings.push(ScheduleWarning::NoCapacityLeft {
+                        employee: employee.clone(),
+                        days: num_days,
+                    });
+                    explanations.insert(employee.id, explain(Placement::Unplaced, Vec::new()));
+                    continue;
+                }
+
+                // Pair rules against partners placed so far: keep the combinations that break
+                // the fewest, so any remaining breach is reported by check_pair_constraints
+                let fewest_breaches = fitting_combos
+                    .iter()
+                    .map(|combo| pair_violations(combo, employee, schedule, generator))
+                    .min()
+                    .unwrap_or(0);
+                let fitting_combos: Vec<DayCombination> = fitting_combos
+                    .into_iter()
+                    .filter(|combo| {
+                        pair_violations(combo, employee, schedule, generator) == fewest_breaches
+                    })
+                    .collect();
+
+                // Role maximums are honoured when possible; if every combination breaks one,
+                // the employee is still placed and the breach is reported by check_coverage_rules
+                let within_max: Vec<DayCombination> = fitting_combos
+                    .iter()
+                    .filter(|combo| {
+                        within_role_maximums(
+                            combo,
+                            employee,
+                            schedule,
+                            &generator.constraints.coverage_rules,
+                        )
+                    })
+                    .cloned()
+                    .collect();
+                let mut candidate_combos = if within_max.is_empty() {
+                    fitting_combos
+                } else {
+                    within_max
+                };
+
+                // Same fallback for the NSP colleague rule, reported by check_nsp_policy
+                if employee.is_nsp && generator.config.nsp_policy.require_colleague_overlap {
+                    let with_colleague: Vec<DayCombination> = candidate_combos
+                        .iter()
+                        .filter(|combo| {
+                            combo
+                                .days
+                                .iter()
+                                .any(|day| has_regular_colleague(schedule, day, employee))
+                        })
+                        .cloned()
+                        .collect();
+                    if !with_colleague.is_empty() {
+                        candidate_combos = with_colleague;
+                    }
+                }
+
+                // Find best day combination
+                let (best_combo, considered) = find_best_day_combination(
+                    &candidate_combos,
+                    day_counts,
+                    employee,
+                    past_schedules,
+                    schedule,
+                    generator,
+                    female_ratio,
+                    rng,
+                );
+
+                // Assign employee to days from the best combination
+                for day in &best_combo.days {
+                    if let Some(daily_schedule) = schedule.get_mut(day) {
+                        if !daily_schedule.iter().any(|e| e.id == employee.id) {
+                            daily_schedule.push(employee.clone());
+                            *day_counts.entry(day.clone()).or_insert(0) += 1;
                         }
                     }
                 }
+                explanations.insert(employee.id, explain(Placement::Flexible, considered));
             }
         }
     }
 }
 
-fn find_best_day_combination(
-    available_combos: &[DayCombination],
+pub fn is_available(days: &[Weekday], employee: &Employee) -> bool {
+    !days
+        .iter()
+        .any(|day| employee.unavailable_days.contains(day))
+}
+
+// Office days outside the employee's preferred days (none if they have no preference)
+fn missed_preferences(days: &[Weekday], employee: &Employee) -> usize {
+    if employee.preferred_days.is_empty() {
+        return 0;
+    }
+    days.iter()
+        .filter(|day| !employee.preferred_days.contains(day))
+        .count()
+}
+
+pub fn fits_capacity(
+    combo: &DayCombination,
     day_counts: &DayCount,
+    config: &SchedulerConfig,
+) -> bool {
+    combo.days.iter().all(|day| match config.capacity_for(day) {
+        Some(capacity) => day_counts.get(day).copied().unwrap_or(0) < capacity,
+        None => true,
+    })
+}
+
+// Desks of the location the employee comes in to, when it has a limit
+pub fn location_capacity(generator: &ScheduleGenerator, employee: &Employee) -> Option<usize> {
+    let location_id = employee.office_location()?;
+    generator
+        .constraints
+        .locations
+        .iter()
+        .find(|location| location.id == location_id)
+        .and_then(|location| location.capacity)
+}
+
+pub fn location_count(schedule: &MonthlySchedule, day: &Weekday, location_id: usize) -> usize {
+    schedule.get(day).map_or(0, |emps| {
+        emps.iter()
+            .filter(|e| e.office_location() == Some(location_id))
+            .count()
+    })
+}
+
+fn fits_location_capacity(
+    combo: &DayCombination,
     employee: &Employee,
-    past_schedules: &PastSchedules,
-) -> DayCombination {
-    let mut rng = rng();
-    let mut shuffled_combos = available_combos.to_vec();
-    shuffled_combos.shuffle(&mut rng);
+    schedule: &MonthlySchedule,
+    generator: &ScheduleGenerator,
+) -> bool {
+    let (Some(location_id), Some(capacity)) = (
+        employee.office_location(),
+        location_capacity(generator, employee),
+    ) else {
+        return true;
+    };
+    combo
+        .days
+        .iter()
+        .all(|day| location_count(schedule, day, location_id) < capacity)
+}
 
-    let mut best_combo = shuffled_combos[0].clone();
-    let mut min_score = f64::INFINITY;
+// Spread of one location's daily headcounts, with `extra_days` added for someone not yet
+// placed. Only counted with two or more locations; with one, the company-wide spread is it.
+fn location_spread(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    location_id: usize,
+    extra_days: &[Weekday],
+) -> f64 {
+    if generator.constraints.locations.len() < 2 {
+        return 0.0;
+    }
+    let counts: Vec<f64> = generator
+        .weekdays
+        .iter()
+        .map(|day| {
+            let extra = extra_days.contains(day) as usize;
+            (location_count(schedule, day, location_id) + extra) as f64
+        })
+        .collect();
+    let avg_count = counts.iter().sum::<f64>() / counts.len().max(1) as f64;
+    counts
+        .iter()
+        .map(|count| (count - avg_count).powi(2))
+        .sum::<f64>()
+}
 
-    // Calculate past day frequencies with recency weighting
+// Daily spread of every location, added to the company-wide spread so each office is
+// balanced on its own and not just the total
+pub fn location_variance(generator: &ScheduleGenerator, schedule: &MonthlySchedule) -> f64 {
+    generator
+        .constraints
+        .locations
+        .iter()
+        .map(|location| location_spread(generator, schedule, location.id, &[]))
+        .sum()
+}
+
+fn nsp_count(schedule: &MonthlySchedule, day: &Weekday) -> usize {
+    schedule
+        .get(day)
+        .map_or(0, |emps| emps.iter().filter(|e| e.is_nsp).count())
+}
+
+pub fn fits_nsp_cap(
+    combo: &DayCombination,
+    schedule: &MonthlySchedule,
+    policy: &NspPolicy,
+) -> bool {
+    match policy.max_per_day {
+        Some(max) => combo.days.iter().all(|day| nsp_count(schedule, day) < max),
+        None => true,
+    }
+}
+
+// A non-NSP in the same role is in the office that day
+fn has_regular_colleague(schedule: &MonthlySchedule, day: &Weekday, employee: &Employee) -> bool {
+    schedule.get(day).is_some_and(|emps| {
+        emps.iter()
+            .any(|e| !e.is_nsp && e.role == employee.role && e.id != employee.id)
+    })
+}
+
+pub fn check_nsp_policy(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    warnings: &mut Vec<ScheduleWarning>,
+) {
+    let policy = &generator.config.nsp_policy;
+
+    if let Some(max) = policy.max_per_day {
+        for day in &generator.weekdays {
+            let count = nsp_count(schedule, day);
+            if count > max {
+                warnings.push(ScheduleWarning::NspCapExceeded {
+                    day: day.clone(),
+                    count,
+                    max,
+                });
+            }
+        }
+    }
+
+    if policy.require_colleague_overlap {
+        warnings.extend(
+            nsps_without_colleague(generator, schedule)
+                .into_iter()
+                .map(ScheduleWarning::NspWithoutColleague),
+        );
+    }
+}
+
+// NSPs with office days, none of which they share with a non-NSP colleague in their role
+fn nsps_without_colleague(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+) -> Vec<Employee> {
+    let mut nsp_days: HashMap<usize, (Employee, Vec<Weekday>)> = HashMap::new();
+    for day in &generator.weekdays {
+        for employee in schedule.get(day).into_iter().flatten().filter(|e| e.is_nsp) {
+            nsp_days
+                .entry(employee.id)
+                .or_insert_with(|| (employee.clone(), Vec::new()))
+                .1
+                .push(day.clone());
+        }
+    }
+
+    let mut lonely: Vec<Employee> = nsp_days
+        .into_values()
+        .filter(|(employee, days)| {
+            !days
+                .iter()
+                .any(|day| has_regular_colleague(schedule, day, employee))
+        })
+        .map(|(employee, _)| employee)
+        .collect();
+    lonely.sort_by(|a, b| a.name.cmp(&b.name));
+    lonely
+}
+
+fn role_count(schedule: &MonthlySchedule, day: &Weekday, role: &Role) -> usize {
+    schedule
+        .get(day)
+        .map_or(0, |emps| emps.iter().filter(|e| &e.role == role).count())
+}
+
+fn within_role_maximums(
+    combo: &DayCombination,
+    employee: &Employee,
+    schedule: &MonthlySchedule,
+    rules: &[CoverageRule],
+) -> bool {
+    combo.days.iter().all(|day| {
+        rules
+            .iter()
+            .filter(|rule| rule.role == employee.role && rule.applies_to(day))
+            .all(|rule| match rule.max_count {
+                Some(max) => role_count(schedule, day, &rule.role) < max,
+                None => true,
+            })
+    })
+}
+
+// Number of still-unmet role minimums this combination would help fill
+fn coverage_gain(
+    combo: &DayCombination,
+    employee: &Employee,
+    schedule: &MonthlySchedule,
+    rules: &[CoverageRule],
+) -> usize {
+    combo
+        .days
+        .iter()
+        .map(|day| {
+            rules
+                .iter()
+                .filter(|rule| rule.role == employee.role && rule.applies_to(day))
+                .filter(|rule| {
+                    rule.min_count
+                        .is_some_and(|min| role_count(schedule, day, &rule.role) < min)
+                })
+                .count()
+        })
+        .sum()
+}
+
+pub fn company_female_ratio(employees: &[Employee]) -> f64 {
+    if employees.is_empty() {
+        return 0.0;
+    }
+    let females = employees.iter().filter(|e| e.sex == Sex::Female).count();
+    females as f64 / employees.len() as f64
+}
+
+// (male, female) headcount on a day
+pub fn sex_counts(schedule: &MonthlySchedule, day: &Weekday) -> (usize, usize) {
+    schedule.get(day).map_or((0, 0), |emps| {
+        let females = emps.iter().filter(|e| e.sex == Sex::Female).count();
+        (emps.len() - females, females)
+    })
+}
+
+// Squared distance of a day's female share from the target, scaled by the day's headcount
+fn sex_imbalance(males: usize, females: usize, female_ratio: f64) -> f64 {
+    let total = males + females;
+    if total == 0 {
+        return 0.0;
+    }
+    let share = females as f64 / total as f64;
+    (share - female_ratio).powi(2) * total as f64
+}
+
+// How much worse (or better, if negative) the days in this combination get balanced
+fn sex_imbalance_increase(
+    combo: &DayCombination,
+    employee: &Employee,
+    schedule: &MonthlySchedule,
+    female_ratio: f64,
+) -> f64 {
+    combo
+        .days
+        .iter()
+        .map(|day| {
+            let (males, females) = sex_counts(schedule, day);
+            let before = sex_imbalance(males, females, female_ratio);
+            let after = match employee.sex {
+                Sex::Male => sex_imbalance(males + 1, females, female_ratio),
+                Sex::Female => sex_imbalance(males, females + 1, female_ratio),
+            };
+            after - before
+        })
+        .sum()
+}
+
+fn team_for<'a>(generator: &'a ScheduleGenerator, employee: &Employee) -> Option<&'a Team> {
+    let team_id = employee.team_id?;
+    generator
+        .constraints
+        .teams
+        .iter()
+        .find(|team| team.id == team_id)
+}
+
+// Office days of each team member already in the schedule, by employee id
+fn team_member_days<'a>(
+    generator: &'a ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    team_id: usize,
+) -> BTreeMap<usize, Vec<&'a Weekday>> {
+    let mut member_days: BTreeMap<usize, Vec<&Weekday>> = BTreeMap::new();
+    for day in &generator.weekdays {
+        for e in schedule.get(day).into_iter().flatten() {
+            if e.team_id == Some(team_id) {
+                member_days.entry(e.id).or_default().push(day);
+            }
+        }
+    }
+    member_days
+}
+
+// Shared days two teammates still miss; the target is capped by whoever comes in less
+fn pair_shortfall(a: &[&Weekday], b: &[&Weekday], target: usize) -> usize {
+    let shared = a.iter().filter(|day| b.contains(day)).count();
+    target.min(a.len()).min(b.len()).saturating_sub(shared)
+}
+
+// Average shortfall between this combination and each teammate placed so far
+fn colocation_shortfall(
+    combo: &DayCombination,
+    employee: &Employee,
+    schedule: &MonthlySchedule,
+    generator: &ScheduleGenerator,
+) -> f64 {
+    let Some(team) = team_for(generator, employee) else {
+        return 0.0;
+    };
+    let mut member_days = team_member_days(generator, schedule, team.id);
+    member_days.remove(&employee.id);
+    if member_days.is_empty() {
+        return 0.0;
+    }
+
+    let combo_days: Vec<&Weekday> = combo.days.iter().collect();
+    let total = member_days
+        .values()
+        .map(|days| pair_shortfall(&combo_days, days, team.colocation_days))
+        .sum::<usize>();
+    total as f64 / member_days.len() as f64
+}
+
+struct TeamColocation<'a> {
+    team: &'a Team,
+    members: usize,
+    pairs: usize,
+    pairs_short: usize,
+    // Sum of every pair's shortfall
+    shortfall: usize,
+}
+
+// Pairwise co-location for every team with two or more members in the office
+fn team_colocation<'a>(
+    generator: &'a ScheduleGenerator,
+    schedule: &MonthlySchedule,
+) -> Vec<TeamColocation<'a>> {
+    generator
+        .constraints
+        .teams
+        .iter()
+        .filter_map(|team| {
+            let member_days: Vec<Vec<&Weekday>> = team_member_days(generator, schedule, team.id)
+                .into_values()
+                .collect();
+            if member_days.len() < 2 {
+                return None;
+            }
+            let mut status = TeamColocation {
+                team,
+                members: member_days.len(),
+                pairs: 0,
+                pairs_short: 0,
+                shortfall: 0,
+            };
+            for (i, a) in member_days.iter().enumerate() {
+                for b in &member_days[i + 1..] {
+                    let short = pair_shortfall(a, b, team.colocation_days);
+                    status.pairs += 1;
+                    status.shortfall += short;
+                    if short > 0 {
+                        status.pairs_short += 1;
+                    }
+                }
+            }
+            Some(status)
+        })
+        .collect()
+}
+
+pub fn check_team_colocation(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    warnings: &mut Vec<ScheduleWarning>,
+) {
+    for status in team_colocation(generator, schedule) {
+        if status.pairs_short > 0 {
+            warnings.push(ScheduleWarning::TeamBelowColocationTarget {
+                team: status.team.name.clone(),
+                pairs_short: status.pairs_short,
+                pairs: status.pairs,
+                target: status.team.colocation_days,
+            });
+        }
+    }
+}
+
+// Office days of one employee in the schedule so far, in weekday order
+fn office_days_of<'a>(
+    generator: &'a ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    employee_id: usize,
+) -> Vec<&'a Weekday> {
+    generator
+        .weekdays
+        .iter()
+        .filter(|day| {
+            schedule
+                .get(*day)
+                .is_some_and(|emps| emps.iter().any(|e| e.id == employee_id))
+        })
+        .collect()
+}
+
+// Days a pair rule is broken by: missing shared days when together, shared days when apart
+pub fn pair_violation(pair: &PairConstraint, a: &[&Weekday], b: &[&Weekday]) -> usize {
+    match pair.kind {
+        PairKind::Together { min_days } => pair_shortfall(a, b, min_days),
+        PairKind::Apart => a.iter().filter(|day| b.contains(day)).count(),
+    }
+}
+
+// Pair rule breaches this combination causes against partners already in the schedule
+fn pair_violations(
+    combo: &DayCombination,
+    employee: &Employee,
+    schedule: &MonthlySchedule,
+    generator: &ScheduleGenerator,
+) -> usize {
+    let combo_days: Vec<&Weekday> = combo.days.iter().collect();
+    generator
+        .constraints
+        .pairs
+        .iter()
+        .filter(|pair| pair.involves(employee.id))
+        .map(|pair| {
+            let partner_days = office_days_of(generator, schedule, pair.partner_of(employee.id));
+            pair_violation(pair, &combo_days, &partner_days)
+        })
+        .sum()
+}
+
+pub fn check_pair_constraints(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    warnings: &mut Vec<ScheduleWarning>,
+) {
+    for pair in &generator.constraints.pairs {
+        let first_days = office_days_of(generator, schedule, pair.first_id);
+        let second_days = office_days_of(generator, schedule, pair.second_id);
+        let shared = first_days
+            .iter()
+            .filter(|day| second_days.contains(day))
+            .count();
+        match pair.kind {
+            PairKind::Together { min_days } => {
+                let required = min_days.min(first_days.len()).min(second_days.len());
+                if shared < required {
+                    warnings.push(ScheduleWarning::PairTogetherShort {
+                        first: pair.first_name.clone(),
+                        second: pair.second_name.clone(),
+                        shared,
+                        required,
+                    });
+                }
+            }
+            PairKind::Apart => {
+                if shared > 0 {
+                    warnings.push(ScheduleWarning::PairApartOverlap {
+                        first: pair.first_name.clone(),
+                        second: pair.second_name.clone(),
+                        shared,
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub fn check_coverage_rules(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    warnings: &mut Vec<ScheduleWarning>,
+) {
+    for day in &generator.weekdays {
+        for rule in &generator.constraints.coverage_rules {
+            if !rule.applies_to(day) {
+                continue;
+            }
+            let count = role_count(schedule, day, &rule.role);
+            if let Some(min) = rule.min_count {
+                if count < min {
+                    warnings.push(ScheduleWarning::CoverageBelowMinimum {
+                        day: day.clone(),
+                        role: rule.role.clone(),
+                        count,
+                        min,
+                    });
+                }
+            }
+            if let Some(max) = rule.max_count {
+                if count > max {
+                    warnings.push(ScheduleWarning::CoverageAboveMaximum {
+                        day: day.clone(),
+                        role: rule.role.clone(),
+                        count,
+                        max,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Recency-weighted count of how often the employee had each weekday in recent months.
+// History is newest first, so the window is the front of the list and the first month
+// counts fully.
+pub fn past_day_frequencies(
+    employee_id: usize,
+    past_schedules: &PastSchedules,
+    config: &SchedulerConfig,
+) -> HashMap<Weekday, f64> {
     let mut past_day_frequencies: HashMap<Weekday, f64> = HashMap::new();
 
     // Set lookback limit
-    let lookback_limit = 2;
+    let lookback_limit = config.lookback_months;
 
     // Calculate day frequencies from past schedules
-    if let Some(past_employee_schedules) = past_schedules.get(&employee.id) {
-        let recent_schedules = if past_employee_schedules.len() > lookback_limit {
-            &past_employee_schedules[past_employee_schedules.len() - lookback_limit..]
-        } else {
-            past_employee_schedules
-        };
+    if let Some(past_employee_schedules) = past_schedules.get(&employee_id) {
+        let recent_schedules =
+            &past_employee_schedules[..past_employee_schedules.len().min(lookback_limit)];
 
         for (i, past_schedule) in recent_schedules.iter().enumerate() {
             // More recent schedules have higher weight
-            let recency_weight = 1.0 - (i as f64 / recent_schedules.len() as f64 * 0.75);
+            let recency_weight =
+                1.0 - (i as f64 / recent_schedules.len() as f64 * config.recency_decay);
 
             for day in past_schedule {
                 *past_day_frequencies.entry(day.clone()).or_insert(0.0) += recency_weight;
@@ -163,6 +898,58 @@ fn find_best_day_combination(
         }
     }
 
+    past_day_frequencies
+}
+
+// Days beyond the employee's long-term share on the given weekdays
+pub fn fairness_cost(generator: &ScheduleGenerator, employee_id: usize, days: &[Weekday]) -> f64 {
+    generator
+        .day_excess
+        .get(&employee_id)
+        .map_or(0.0, |excess| {
+            days.iter()
+                .map(|day| excess.get(day).copied().unwrap_or(0.0))
+                .sum()
+        })
+}
+
+// Fairness cost of everyone's days in the schedule (unweighted)
+fn fairness_score(generator: &ScheduleGenerator, schedule: &MonthlySchedule) -> f64 {
+    generator
+        .weekdays
+        .iter()
+        .map(|day| {
+            schedule
+                .get(day)
+                .into_iter()
+                .flatten()
+                .map(|employee| fairness_cost(generator, employee.id, std::slice::from_ref(day)))
+                .sum::<f64>()
+        })
+        .sum()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn find_best_day_combination(
+    available_combos: &[DayCombination],
+    day_counts: &DayCount,
+    employee: &Employee,
+    past_schedules: &PastSchedules,
+    schedule: &MonthlySchedule,
+    generator: &ScheduleGenerator,
+    female_ratio: f64,
+    rng: &mut StdRng,
+) -> (DayCombination, Vec<CombinationScore>) {
+    let mut shuffled_combos = available_combos.to_vec();
+    shuffled_combos.shuffle(rng);
+
+    let mut best_combo = shuffled_combos[0].clone();
+    let mut min_score = f64::INFINITY;
+    let mut considered = Vec::with_capacity(shuffled_combos.len());
+
+    // Calculate past day frequencies with recency weighting
+    let past_day_frequencies = past_day_frequencies(employee.id, past_schedules, &generator.config);
+
     for combo in &shuffled_combos {
         // Create temp counts to evaluate this combination
         let mut temp_counts = day_counts.clone();
@@ -170,13 +957,20 @@ fn find_best_day_combination(
             *temp_counts.entry(day.clone()).or_insert(0) += 1;
         }
 
-        // Calculate variance as measure of balance
-        let values: Vec<usize> = temp_counts.values().cloned().collect();
+        // Calculate variance as measure of balance (in week order, so seeded runs repeat exactly)
+        let values: Vec<usize> = generator
+            .weekdays
+            .iter()
+            .map(|day| temp_counts.get(day).copied().unwrap_or(0))
+            .collect();
         let avg_count = values.iter().sum::<usize>() as f64 / values.len() as f64;
         let variance = values
             .iter()
             .map(|&count| (count as f64 - avg_count).powi(2))
-            .sum::<f64>();
+            .sum::<f64>()
+            + employee.office_location().map_or(0.0, |location_id| {
+                location_spread(generator, schedule, location_id, &combo.days)
+            });
 
         // Calculate repetition score
         let repetition_score = combo
@@ -185,9 +979,49 @@ fn find_best_day_combination(
             .map(|day| past_day_frequencies.get(day).unwrap_or(&0.0))
             .sum::<f64>();
 
+        // Turns unpopular days over to whoever has had fewer of them in the ledger window
+        let fairness_score = fairness_cost(generator, employee.id, &combo.days);
+
+        // Filling a role minimum outweighs a small imbalance
+        let coverage_score = coverage_gain(
+            combo,
+            employee,
+            schedule,
+            &generator.constraints.coverage_rules,
+        ) as f64;
+
+        let sex_balance_score = if generator.config.sex_balance_weight > 0.0 {
+            sex_imbalance_increase(combo, employee, schedule, female_ratio)
+        } else {
+            0.0
+        };
+
+        let preference_score = missed_preferences(&combo.days, employee) as f64;
+
+        // Keeps teammates on the same days
+        let colocation_score = colocation_shortfall(combo, employee, schedule, generator);
+
         // Combined score
-        let repetition_weight = 3.0;
-        let total_score = variance + (repetition_weight * repetition_score);
+        let total_score = variance
+            + (generator.config.repetition_weight * repetition_score)
+            + (generator.config.fairness_weight * fairness_score)
+            - (COVERAGE_WEIGHT * coverage_score)
+            + (generator.config.sex_balance_weight * sex_balance_score)
+            + (generator.config.preference_weight * preference_score)
+            + (generator.config.colocation_weight * colocation_score);
+
+        considered.push(CombinationScore {
+            days: combo.days.clone(),
+            variance,
+            repetition: generator.config.repetition_weight * repetition_score,
+            fairness: generator.config.fairness_weight * fairness_score,
+            coverage: 0.0 - COVERAGE_WEIGHT * coverage_score,
+            sex_balance: generator.config.sex_balance_weight * sex_balance_score,
+            preference: generator.config.preference_weight * preference_score,
+            colocation: generator.config.colocation_weight * colocation_score,
+            total: total_score,
+            chosen: false,
+        });
 
         if total_score < min_score {
             min_score = total_score;
@@ -195,7 +1029,42 @@ fn find_best_day_combination(
         }
     }
 
-    best_combo
+    // Ties keep their shuffled order, so the first row is the one that was picked
+    considered.sort_by(|a, b| a.total.total_cmp(&b.total));
+    (best_combo, considered)
+}
+
+fn explain(placement: Placement, considered: Vec<CombinationScore>) -> EmployeeExplanation {
+    EmployeeExplanation {
+        placement,
+        considered,
+    }
+}
+
+// Refinement and the exact solver can move people after the greedy pass scored them,
+// so the chosen row and the placement are read off the final schedule
+fn finalize_explanations(generator: &ScheduleGenerator, result: &mut ScheduleResult) {
+    for (id, explanation) in result.explanations.iter_mut() {
+        let final_days: Vec<&Weekday> = generator
+            .weekdays
+            .iter()
+            .filter(|day| {
+                result
+                    .schedule
+                    .get(day)
+                    .is_some_and(|emps| emps.iter().any(|e| e.id == *id))
+            })
+            .collect();
+        for score in explanation.considered.iter_mut() {
+            score.chosen = score.days.len() == final_days.len()
+                && score.days.iter().all(|day| final_days.contains(&day));
+        }
+        explanation.placement = match explanation.placement {
+            Placement::Flexible if final_days.is_empty() => Placement::Unplaced,
+            Placement::Unplaced if !final_days.is_empty() => Placement::Flexible,
+            ref placement => placement.clone(),
+        };
+    }
 }
 
 // pub fn generate_statistics(
@@ -254,17 +1123,860 @@ fn find_best_day_combination(
 //     }
 // }
 
+// Spread of the daily headcounts around their mean, over the days the schedule covers
+pub fn day_count_variance(generator: &ScheduleGenerator, schedule: &MonthlySchedule) -> f64 {
+    let counts: Vec<f64> = generator
+        .weekdays
+        .iter()
+        .filter_map(|day| schedule.get(day).map(|emps| emps.len() as f64))
+        .collect();
+    let avg_count = counts.iter().sum::<f64>() / counts.len().max(1) as f64;
+    counts
+        .iter()
+        .map(|count| (count - avg_count).powi(2))
+        .sum::<f64>()
+}
+
+// How much everyone's days repeat their recent history (unweighted)
+pub fn repetition_score(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    past_schedules: &PastSchedules,
+) -> f64 {
+    let mut employee_days: BTreeMap<usize, Vec<&Weekday>> = BTreeMap::new();
+    for day in &generator.weekdays {
+        for employee in schedule.get(day).into_iter().flatten() {
+            employee_days.entry(employee.id).or_default().push(day);
+        }
+    }
+    employee_days
+        .iter()
+        .map(|(id, days)| {
+            let frequencies = past_day_frequencies(*id, past_schedules, &generator.config);
+            days.iter()
+                .map(|day| frequencies.get(*day).unwrap_or(&0.0))
+                .sum::<f64>()
+        })
+        .sum::<f64>()
+}
+
+// Whole-schedule counterpart of the greedy combination score: lower is better.
+// Unmet minimums, breached maximums, lonely NSPs and pair rule days cost COVERAGE_WEIGHT each.
+pub fn schedule_objective(
+    generator: &ScheduleGenerator,
+    schedule: &MonthlySchedule,
+    past_schedules: &PastSchedules,
+    female_ratio: f64,
+) -> f64 {
+    let variance = day_count_variance(generator, schedule) + location_variance(generator, schedule);
+    let repetition_score = repetition_score(generator, schedule, past_schedules);
+    let fairness_score = fairness_score(generator, schedule);
+
+    let mut coverage_penalty = 0;
+    for day in &generator.weekdays {
+        for rule in &generator.constraints.coverage_rules {
+            if !rule.applies_to(day) {
+                continue;
+            }
+            let count = role_count(schedule, day, &rule.role);
+            coverage_penalty += rule.min_count.map_or(0, |min| min.saturating_sub(count));
+            coverage_penalty += rule.max_count.map_or(0, |max| count.saturating_sub(max));
+        }
+    }
+    if generator.config.nsp_policy.require_colleague_overlap {
+        coverage_penalty += nsps_without_colleague(generator, schedule).len();
+    }
+    for pair in &generator.constraints.pairs {
+        coverage_penalty += pair_violation(
+            pair,
+            &office_days_of(generator, schedule, pair.first_id),
+            &office_days_of(generator, schedule, pair.second_id),
+        );
+    }
+
+    let sex_balance_score = if generator.config.sex_balance_weight > 0.0 {
+        generator
+            .weekdays
+            .iter()
+            .map(|day| {
+                let (males, females) = sex_counts(schedule, day);
+                sex_imbalance(males, females, female_ratio)
+            })
+            .sum::<f64>()
+    } else {
+        0.0
+    };
+
+    let preference_score = generator
+        .weekdays
+        .iter()
+        .map(|day| {
+            schedule.get(day).map_or(0, |emps| {
+                emps.iter()
+                    .filter(|e| !e.preferred_days.is_empty() && !e.preferred_days.contains(day))
+                    .count()
+            })
+        })
+        .sum::<usize>();
+
+    // Each member's average shortfall against their teammates, as in the greedy score
+    let colocation_score = team_colocation(generator, schedule)
+        .iter()
+        .map(|status| 2.0 * status.shortfall as f64 / (status.members - 1) as f64)
+        .sum::<f64>();
+
+    variance
+        + (generator.config.repetition_weight * repetition_score)
+        + (generator.config.fairness_weight * fairness_score)
+        + (COVERAGE_WEIGHT * coverage_penalty as f64)
+        + (generator.config.sex_balance_weight * sex_balance_score)
+        + (generator.config.preference_weight * preference_score as f64)
+        + (generator.config.colocation_weight * colocation_score)
+}
+
 // Main function to generate balanced office schedules
 pub fn generate_balanced_schedule(
     employees: &[Employee],
     past_schedules: &PastSchedules,
-) -> MonthlySchedule {
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    seed: Option<u64>,
+) -> ScheduleResult {
     // return value (MonthlySchedule, ScheduleStatistics)
-    let generator = ScheduleGenerator::new();
-    let schedule = generate_schedule(&generator, employees, past_schedules);
+    let generator = ScheduleGenerator::new(config.clone(), constraints.clone());
+    // One RNG for the whole run, so the same seed and inputs give the same schedule
+    let seed = seed.unwrap_or_else(|| rand::rng().random());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut schedule = match config.solver_mode {
+        SolverMode::Greedy => generate_schedule(&generator, employees, past_schedules, &mut rng),
+        SolverMode::Exact => solve_exact(&generator, employees, past_schedules, &mut rng),
+    };
+    schedule.seed = seed;
+    finalize_explanations(&generator, &mut schedule);
 
     // let statistics = generate_statistics(&generator.weekdays, &schedule, employees);
 
     // (schedule, statistics)
     schedule
 }
+
+// Pins given per date, turned into weekday pins for one week of the month
+fn weekday_pins(
+    date_pins: &HashMap<usize, Vec<NaiveDate>>,
+    week_dates: &[NaiveDate],
+) -> HashMap<usize, Vec<Weekday>> {
+    date_pins
+        .iter()
+        .filter_map(|(id, dates)| {
+            let days: Vec<Weekday> = week_dates
+                .iter()
+                .filter(|date| dates.contains(date))
+                .map(|date| weekday_of(*date))
+                .collect();
+            (!days.is_empty()).then_some((*id, days))
+        })
+        .collect()
+}
+
+// Weekdays of the week that the office is closed
+fn closed_days_in(closures: &[Closure], week_dates: &[NaiveDate]) -> Vec<Weekday> {
+    week_dates
+        .iter()
+        .filter(|date| closures.iter().any(|closure| closure.date == **date))
+        .map(|date| weekday_of(*date))
+        .collect()
+}
+
+// Weekdays each employee is on leave during the week. A week whose working dates are all
+// leave counts as the whole week away, even when it is cut short by the month or a closure.
+fn leave_days_in(
+    leave: &[Leave],
+    closures: &[Closure],
+    week_dates: &[NaiveDate],
+) -> HashMap<usize, Vec<Weekday>> {
+    let open_dates: Vec<NaiveDate> = week_dates
+        .iter()
+        .copied()
+        .filter(|date| !closures.iter().any(|closure| closure.date == *date))
+        .collect();
+
+    let mut leave_days: HashMap<usize, Vec<Weekday>> = HashMap::new();
+    for period in leave {
+        for day in open_dates
+            .iter()
+            .filter(|date| period.covers(**date))
+            .map(|date| weekday_of(*date))
+        {
+            let days = leave_days.entry(period.employee_id).or_default();
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+    for days in leave_days.values_mut() {
+        if days.len() == open_dates.len() {
+            *days = Weekday::all();
+        }
+    }
+    leave_days
+}
+
+// Office days left for a flexible employee who is on leave for some of the week's open days.
+// Nobody knows which days they would have picked, so the target shrinks in proportion to the
+// share of the week they are away, rounding half up: 2 days with 2 of 5 days off leaves 1,
+// with 1 of 5 off still 2.
+fn prorate_for_leave(required: usize, leave_days: usize, open_days: usize) -> usize {
+    if open_days == 0 {
+        return 0;
+    }
+    let working = open_days.saturating_sub(leave_days);
+    (required * working * 2 + open_days) / (open_days * 2)
+}
+
+// Employees as they stand in a week with closed days or leave, with the office days each owes
+// that week written to `week_required_days`. The target starts from the required days as
+// raised by the NSP policy. With make-up days, someone whose fixed day is closed keeps their
+// other fixed days pinned and gets the lost day back elsewhere, and flexible employees keep
+// their target. Without, each closed day costs everyone a day. Leave takes fixed-day
+// employees off those fixed days and pro-rates a flexible employee's target (see
+// `prorate_for_leave`); days of leave become unavailable. Anyone left with no days that week
+// is left out.
+fn employees_for_week(
+    generator: &ScheduleGenerator,
+    employees: &[Employee],
+    pins: &mut HashMap<usize, Vec<Weekday>>,
+    week_required_days: &mut HashMap<usize, usize>,
+) -> Vec<Employee> {
+    let closed_days = &generator.constraints.closed_days;
+    let make_up = generator.config.make_up_closed_days;
+    let open_days = generator.weekdays.len();
+    for (id, days) in pins.iter_mut() {
+        let on_leave = generator.constraints.leave_days.get(id);
+        days.retain(|day| {
+            !closed_days.contains(day) && on_leave.is_none_or(|leave| !leave.contains(day))
+        });
+    }
+
+    let mut week_employees = Vec::new();
+    for employee in employees {
+        let mut employee = employee.clone();
+        let on_leave: Vec<Weekday> = generator
+            .constraints
+            .leave_days
+            .get(&employee.id)
+            .into_iter()
+            .flatten()
+            .filter(|day| generator.weekdays.contains(day))
+            .cloned()
+            .collect();
+        if open_days > 0 && on_leave.len() == open_days {
+            continue;
+        }
+
+        let mut required = generator.config.nsp_policy.required_days_for(&employee);
+        if employee.fixed_days.is_empty() {
+            if !make_up {
+                required = required.saturating_sub(closed_days.len());
+            }
+        } else {
+            let kept: Vec<Weekday> = employee
+                .fixed_days
+                .iter()
+                .filter(|day| !closed_days.contains(day))
+                .cloned()
+                .collect();
+            if kept.len() < employee.fixed_days.len() {
+                if make_up {
+                    required = employee.fixed_days.len();
+                    employee.fixed_days.clear();
+                    let employee_pins = pins.entry(employee.id).or_default();
+                    for day in kept.into_iter().filter(|day| !on_leave.contains(day)) {
+                        if !employee_pins.contains(&day) {
+                            employee_pins.push(day);
+                        }
+                    }
+                } else if kept.is_empty() {
+                    continue;
+                } else {
+                    employee.fixed_days = kept;
+                }
+            }
+        }
+
+        if !on_leave.is_empty() {
+            if employee.fixed_days.is_empty() {
+                required = prorate_for_leave(required, on_leave.len(), open_days);
+            } else {
+                employee.fixed_days.retain(|day| !on_leave.contains(day));
+                if employee.fixed_days.is_empty() {
+                    continue;
+                }
+            }
+            for day in on_leave {
+                if !employee.unavailable_days.contains(&day) {
+                    employee.unavailable_days.push(day);
+                }
+            }
+        }
+
+        if !employee.fixed_days.is_empty() {
+            required = employee.fixed_days.len();
+        }
+        let required = required.min(open_days);
+        employee.required_days = required as u8;
+        week_required_days.insert(employee.id, required);
+        week_employees.push(employee);
+    }
+    week_employees
+}
+
+// Schedules a week with closed days or leave, then puts the unchanged employee records back
+// into the schedule so nothing downstream sees the adjusted ones
+fn generate_week_with_absences(
+    employees: &[Employee],
+    past_schedules: &PastSchedules,
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    seed: u64,
+) -> ScheduleResult {
+    let generator = ScheduleGenerator::new(config.clone(), constraints.clone());
+    let mut pins = constraints.pins.clone();
+    let mut week_required_days = HashMap::new();
+    let week_employees =
+        employees_for_week(&generator, employees, &mut pins, &mut week_required_days);
+    let week_constraints = ScheduleConstraints {
+        pins,
+        week_required_days,
+        ..constraints.clone()
+    };
+
+    let mut result = generate_balanced_schedule(
+        &week_employees,
+        past_schedules,
+        config,
+        &week_constraints,
+        Some(seed),
+    );
+    for day_employees in result.schedule.values_mut() {
+        for employee in day_employees.iter_mut() {
+            if let Some(original) = employees.iter().find(|e| e.id == employee.id) {
+                *employee = original.clone();
+            }
+        }
+    }
+    for employee in employees {
+        if !week_employees.iter().any(|e| e.id == employee.id) {
+            let placement = if constraints.leave_days.contains_key(&employee.id) {
+                Placement::OnLeave
+            } else {
+                Placement::Unplaced
+            };
+            result
+                .explanations
+                .insert(employee.id, explain(placement, Vec::new()));
+        }
+    }
+    result
+}
+
+// Schedules every calendar week of the month. Without weekly rotation every week gets the
+// same pattern unless its pins or closures differ; with it, week i is generated from seed + i
+// and sees the earlier weeks as its most recent history, so people's days move from week to
+// week. Closed dates are left empty and nobody is placed on a day of leave. The exact solver's
+// time budget covers the whole month, shared out over the weeks.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_month_schedule(
+    employees: &[Employee],
+    past_schedules: &PastSchedules,
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    year: i32,
+    month: u32,
+    date_pins: &HashMap<usize, Vec<NaiveDate>>,
+    seed: Option<u64>,
+) -> Vec<ScheduleWeek> {
+    let seed = seed.unwrap_or_else(|| rand::rng().random());
+    let mut history = past_schedules.clone();
+    let mut first_week: Option<(ScheduleConstraints, ScheduleResult)> = None;
+    let mut weeks = Vec::new();
+    let deadline = constraints
+        .solver_deadline
+        .unwrap_or_else(|| solver_deadline(config));
+    let month_dates = month_weeks(year, month, &config.work_days);
+    let week_count = month_dates.len();
+
+    for (index, dates) in month_dates.into_iter().enumerate() {
+        let week_constraints = ScheduleConstraints {
+            pins: weekday_pins(date_pins, &dates),
+            closed_days: closed_days_in(&constraints.closures, &dates),
+            leave_days: leave_days_in(&constraints.leave, &constraints.closures, &dates),
+            solver_deadline: Some(share_of_budget(deadline, week_count - index)),
+            ..constraints.clone()
+        };
+        let repeat = first_week
+            .as_ref()
+            .filter(|(first, _)| {
+                !config.weekly_rotation
+                    && first.pins == week_constraints.pins
+                    && first.closed_days == week_constraints.closed_days
+                    && first.leave_days == week_constraints.leave_days
+            })
+            .map(|(_, result)| result.clone());
+
+        let result = match repeat {
+            Some(result) => result,
+            None => {
+                let week_seed = if config.weekly_rotation {
+                    seed.wrapping_add(index as u64)
+                } else {
+                    seed
+                };
+                if week_constraints.closed_days.is_empty() && week_constraints.leave_days.is_empty()
+                {
+                    generate_balanced_schedule(
+                        employees,
+                        &history,
+                        config,
+                        &week_constraints,
+                        Some(week_seed),
+                    )
+                } else {
+                    generate_week_with_absences(
+                        employees,
+                        &history,
+                        config,
+                        &week_constraints,
+                        week_seed,
+                    )
+                }
+            }
+        };
+
+        if config.weekly_rotation {
+            // Newest first, as the months loaded from the database are
+            for (id, entries) in history.iter_mut() {
+                let days: HashSet<Weekday> = result
+                    .schedule
+                    .iter()
+                    .filter(|(_, emps)| emps.iter().any(|e| e.id == *id))
+                    .map(|(day, _)| day.clone())
+                    .collect();
+                entries.insert(0, days);
+                entries.truncate(config.lookback_months);
+            }
+        }
+        if first_week.is_none() {
+            first_week = Some((week_constraints, result.clone()));
+        }
+
+        let mut week = ScheduleWeek { dates, result };
+        restrict_to_dates(&mut week.result.schedule, &week.dates);
+        weeks.push(week);
+    }
+
+    weeks
+}
+
+// Employee ids per date, to tell whether two month schedules are the same
+fn schedule_key(weeks: &[ScheduleWeek]) -> Vec<(NaiveDate, Vec<usize>)> {
+    to_dated(weeks)
+        .into_iter()
+        .map(|(date, mut ids)| {
+            ids.sort();
+            (date, ids)
+        })
+        .collect()
+}
+
+// Warnings about someone who could not be placed, as opposed to a rule the schedule breaks
+fn placement_failure(warning: &ScheduleWarning) -> Option<&Employee> {
+    match warning {
+        ScheduleWarning::UnplacedEmployee { employee, .. }
+        | ScheduleWarning::NoAvailableCombination { employee, .. }
+        | ScheduleWarning::NoCapacityLeft { employee, .. } => Some(employee),
+        _ => None,
+    }
+}
+
+// Rules the month breaks. A weekly pattern repeats its breaches every week, so identical
+// warnings from different weeks count once.
+fn distinct_breaches(weeks: &[ScheduleWeek]) -> usize {
+    let mut breaches: Vec<&ScheduleWarning> = Vec::new();
+    for warning in weeks.iter().flat_map(|week| &week.result.warnings) {
+        if placement_failure(warning).is_none() && !breaches.contains(&warning) {
+            breaches.push(warning);
+        }
+    }
+    breaches.len()
+}
+
+fn unplaced_employees(weeks: &[ScheduleWeek]) -> usize {
+    weeks
+        .iter()
+        .flat_map(|week| &week.result.warnings)
+        .filter_map(placement_failure)
+        .map(|employee| employee.id)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+// Generates up to `count` distinct month schedules from fresh seeds so they can be compared.
+// Seeds that reproduce an earlier schedule are skipped, giving up after twice `count` runs.
+// The exact solver's time budget covers all of them, shared out over the options still to
+// find.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_schedule_options(
+    employees: &[Employee],
+    past_schedules: &PastSchedules,
+    config: &SchedulerConfig,
+    constraints: &ScheduleConstraints,
+    year: i32,
+    month: u32,
+    date_pins: &HashMap<usize, Vec<NaiveDate>>,
+    count: usize,
+) -> Vec<ScheduleOption> {
+    let generator = ScheduleGenerator::new(config.clone(), constraints.clone());
+    let mut options: Vec<ScheduleOption> = Vec::with_capacity(count);
+    let mut seen = Vec::new();
+    let deadline = solver_deadline(config);
+
+    for _ in 0..count * 2 {
+        if options.len() == count {
+            break;
+        }
+        let run_constraints = ScheduleConstraints {
+            solver_deadline: Some(share_of_budget(deadline, count - options.len())),
+            ..constraints.clone()
+        };
+        let weeks = generate_month_schedule(
+            employees,
+            past_schedules,
+            config,
+            &run_constraints,
+            year,
+            month,
+            date_pins,
+            None,
+        );
+        let key = schedule_key(&weeks);
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+
+        let schedules = || weeks.iter().map(|week| &week.result.schedule);
+        options.push(ScheduleOption {
+            day_counts: generator
+                .weekdays
+                .iter()
+                .map(|day| {
+                    let count = schedules()
+                        .map(|schedule| schedule.get(day).map_or(0, |emps| emps.len()))
+                        .sum();
+                    (day.clone(), count)
+                })
+                .collect(),
+            variance: schedules()
+                .map(|schedule| day_count_variance(&generator, schedule))
+                .sum(),
+            repetition: schedules()
+                .map(|schedule| repetition_score(&generator, schedule, past_schedules))
+                .sum(),
+            breaches: distinct_breaches(&weeks),
+            unplaced: unplaced_employees(&weeks),
+            weeks,
+        });
+    }
+
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::schema::LeaveKind;
+    use crate::server::test_support::employee;
+    use chrono::Datelike;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn leave(employee_id: usize, start: NaiveDate, end: NaiveDate) -> Leave {
+        Leave {
+            id: 0,
+            employee_id,
+            kind: LeaveKind::Annual,
+            start,
+            end,
+        }
+    }
+
+    fn nsp_config(required_days: u8) -> SchedulerConfig {
+        SchedulerConfig {
+            nsp_policy: NspPolicy {
+                required_days: Some(required_days),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    // Office days of one employee in the week starting on `monday`
+    fn days_in_week(weeks: &[ScheduleWeek], monday: NaiveDate, id: usize) -> Vec<Weekday> {
+        let week = weeks.iter().find(|week| week.dates[0] == monday).unwrap();
+        let mut days: Vec<Weekday> = week
+            .result
+            .schedule
+            .iter()
+            .filter(|(_, emps)| emps.iter().any(|e| e.id == id))
+            .map(|(day, _)| day.clone())
+            .collect();
+        days.sort_by_key(|day| day.days_from_monday());
+        days
+    }
+
+    #[test]
+    fn leave_prorates_flexible_days() {
+        assert_eq!(prorate_for_leave(2, 0, 5), 2);
+        assert_eq!(prorate_for_leave(2, 1, 5), 2);
+        assert_eq!(prorate_for_leave(2, 2, 5), 1);
+        assert_eq!(prorate_for_leave(4, 2, 5), 2);
+        assert_eq!(prorate_for_leave(3, 4, 5), 1);
+        assert_eq!(prorate_for_leave(1, 4, 5), 0);
+    }
+
+    #[test]
+    fn nsp_on_leave_gets_the_prorated_policy_target() {
+        let mut nsp = employee(1, 2);
+        nsp.is_nsp = true;
+        let employees = vec![nsp, employee(2, 2), employee(3, 3)];
+        let constraints = ScheduleConstraints {
+            leave: vec![leave(1, date(2026, 11, 2), date(2026, 11, 3))],
+            ..Default::default()
+        };
+
+        let weeks = generate_month_schedule(
+            &employees,
+            &PastSchedules::new(),
+            &nsp_config(4),
+            &constraints,
+            2026,
+            11,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        // 4 policy days with 2 of 5 days on leave
+        let days = days_in_week(&weeks, date(2026, 11, 2), 1);
+        assert_eq!(days.len(), 2, "{:?}", days);
+        assert!(!days.contains(&Weekday::Monday) && !days.contains(&Weekday::Tuesday));
+        // Weeks without leave keep the full policy target
+        assert_eq!(days_in_week(&weeks, date(2026, 11, 9), 1).len(), 4);
+    }
+
+    #[test]
+    fn closures_reduce_the_nsp_policy_target() {
+        let mut nsp = employee(1, 2);
+        nsp.is_nsp = true;
+        let config = SchedulerConfig {
+            make_up_closed_days: false,
+            ..nsp_config(4)
+        };
+        let constraints = ScheduleConstraints {
+            closures: vec![Closure {
+                id: 0,
+                date: date(2026, 11, 4),
+                name: "Closed".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let weeks = generate_month_schedule(
+            &[nsp, employee(2, 2)],
+            &PastSchedules::new(),
+            &config,
+            &constraints,
+            2026,
+            11,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        assert_eq!(days_in_week(&weeks, date(2026, 11, 2), 1).len(), 3);
+    }
+
+    #[test]
+    fn flexible_leave_only_costs_days_in_proportion() {
+        let employees = vec![employee(1, 2), employee(2, 2)];
+        let constraints = ScheduleConstraints {
+            leave: vec![
+                leave(1, date(2026, 11, 2), date(2026, 11, 2)),
+                leave(2, date(2026, 11, 2), date(2026, 11, 3)),
+            ],
+            ..Default::default()
+        };
+
+        let weeks = generate_month_schedule(
+            &employees,
+            &PastSchedules::new(),
+            &SchedulerConfig::default(),
+            &constraints,
+            2026,
+            11,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        let monday = date(2026, 11, 2);
+        assert_eq!(days_in_week(&weeks, monday, 1).len(), 2);
+        assert_eq!(days_in_week(&weeks, monday, 2).len(), 1);
+        assert!(!days_in_week(&weeks, monday, 2).contains(&Weekday::Tuesday));
+    }
+
+    #[test]
+    fn breaches_repeated_across_weeks_count_once() {
+        let below = ScheduleWarning::CoverageBelowMinimum {
+            day: Weekday::Monday,
+            role: Role::HR,
+            count: 0,
+            min: 1,
+        };
+        let apart = ScheduleWarning::PairApartOverlap {
+            first: "Ama".to_string(),
+            second: "Kofi".to_string(),
+            shared: 1,
+        };
+        let unplaced = ScheduleWarning::NoCapacityLeft {
+            employee: employee(3, 2),
+            days: 2,
+        };
+        let week = |warnings: Vec<ScheduleWarning>| ScheduleWeek {
+            dates: Vec::new(),
+            result: ScheduleResult {
+                warnings,
+                ..Default::default()
+            },
+        };
+        let weeks = vec![
+            week(vec![below.clone(), unplaced.clone()]),
+            week(vec![below.clone(), apart, unplaced.clone()]),
+            week(vec![below]),
+        ];
+
+        assert_eq!(distinct_breaches(&weeks), 2);
+        assert_eq!(unplaced_employees(&weeks), 1);
+    }
+
+    #[test]
+    fn warning_names_the_target_that_was_used() {
+        let mut nsp = employee(1, 1);
+        nsp.is_nsp = true;
+        nsp.unavailable_days = vec![Weekday::Wednesday, Weekday::Thursday, Weekday::Friday];
+        let constraints = ScheduleConstraints {
+            leave: vec![leave(1, date(2026, 11, 2), date(2026, 11, 2))],
+            ..Default::default()
+        };
+
+        let weeks = generate_month_schedule(
+            &[nsp],
+            &PastSchedules::new(),
+            &nsp_config(3),
+            &constraints,
+            2026,
+            11,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        // 3 policy days less a fifth of the week is 2, and only Tuesday is left
+        let week = weeks
+            .iter()
+            .find(|week| week.dates[0] == date(2026, 11, 2))
+            .unwrap();
+        let messages: Vec<String> = week.result.warnings.iter().map(|w| w.to_string()).collect();
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("every 2-day combination includes one of their unavailable")),
+            "{:?}",
+            messages
+        );
+    }
+
+    #[test]
+    fn partial_weeks_only_hold_dates_inside_the_month() {
+        let employees: Vec<Employee> = (1..=4).map(|id| employee(id, 2)).collect();
+
+        // 1 April 2026 is a Wednesday and 30 April a Thursday
+        let weeks = generate_month_schedule(
+            &employees,
+            &PastSchedules::new(),
+            &SchedulerConfig::default(),
+            &ScheduleConstraints::default(),
+            2026,
+            4,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(
+            weeks[0].dates,
+            vec![date(2026, 4, 1), date(2026, 4, 2), date(2026, 4, 3)]
+        );
+        assert_eq!(weeks[4].dates.last(), Some(&date(2026, 4, 30)));
+        for week in &weeks {
+            assert!(week.dates.iter().all(|d| d.month() == 4));
+            let mut days: Vec<Weekday> = week.result.schedule.keys().cloned().collect();
+            days.sort_by_key(|day| day.days_from_monday());
+            let in_month: Vec<Weekday> = week.dates.iter().map(|d| weekday_of(*d)).collect();
+            assert_eq!(days, in_month);
+        }
+        // Full weeks give everyone their days
+        for id in 1..=4 {
+            assert_eq!(days_in_week(&weeks, date(2026, 4, 6), id).len(), 2);
+        }
+    }
+
+    #[test]
+    fn closed_day_is_made_up_within_the_week() {
+        let mut fixed = employee(1, 2);
+        fixed.fixed_days = vec![Weekday::Monday, Weekday::Wednesday];
+        let employees = vec![fixed, employee(2, 3), employee(3, 2)];
+        let constraints = ScheduleConstraints {
+            closures: vec![Closure {
+                id: 0,
+                date: date(2026, 11, 4),
+                name: "Closed".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let weeks = generate_month_schedule(
+            &employees,
+            &PastSchedules::new(),
+            &SchedulerConfig::default(),
+            &constraints,
+            2026,
+            11,
+            &HashMap::new(),
+            Some(7),
+        );
+
+        let monday = date(2026, 11, 2);
+        let fixed_days = days_in_week(&weeks, monday, 1);
+        assert_eq!(fixed_days.len(), 2, "{:?}", fixed_days);
+        assert!(fixed_days.contains(&Weekday::Monday));
+        for id in 1..=3 {
+            assert!(!days_in_week(&weeks, monday, id).contains(&Weekday::Wednesday));
+        }
+        assert_eq!(days_in_week(&weeks, monday, 2).len(), 3);
+        assert!(to_dated(&weeks)[&date(2026, 11, 4)].is_empty());
+        // The fixed days come back once the office is open all week
+        assert_eq!(
+            days_in_week(&weeks, date(2026, 11, 9), 1),
+            vec![Weekday::Monday, Weekday::Wednesday]
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use chrono::{NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
+    time::Instant,
 };
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
@@ -13,6 +15,32 @@ pub struct Employee {
     pub required_days: u8,
     pub fixed_days: Vec<Weekday>,
     pub is_nsp: bool,
+    // Soft: the scheduler leans towards these days
+    #[serde(default)]
+    pub preferred_days: Vec<Weekday>,
+    // Hard: the scheduler never picks these days for a flexible employee
+    #[serde(default)]
+    pub unavailable_days: Vec<Weekday>,
+    #[serde(default)]
+    pub team_id: Option<usize>,
+    #[serde(default)]
+    pub location_id: Option<usize>,
+    // Office they come in to when it is not their home location
+    #[serde(default)]
+    pub attends_location_id: Option<usize>,
+    // Desk equipment they need; desks with it are picked first
+    #[serde(default)]
+    pub desk_needs: Vec<DeskAttribute>,
+    // Drives in; gets a parking space on office days when one is free
+    #[serde(default)]
+    pub needs_parking: bool,
+}
+
+impl Employee {
+    // Location whose desks the employee takes on office days
+    pub fn office_location(&self) -> Option<usize> {
+        self.attends_location_id.or(self.location_id)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
@@ -77,6 +105,30 @@ impl fmt::Display for Role {
     }
 }
 
+impl Role {
+    pub fn values() -> &'static [Role] {
+        &[
+            Role::HR,
+            Role::AiLlmEngineer,
+            Role::SocialMediaMarketing,
+            Role::ITSupport,
+            Role::MLEngineer,
+            Role::DataScientist,
+            Role::DataAnalyst,
+            Role::FullStackEngineer,
+            Role::BackendEngineer,
+            Role::FrontendEngineer,
+            Role::BlockchainEngineer,
+            Role::QaEngineer,
+            Role::ProjectManager,
+            Role::UiUxDesigner,
+            Role::MobileEngineer,
+            Role::DevOpsEngineer,
+            Role::OperationsManager,
+        ]
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
 pub enum Weekday {
     Monday,
@@ -84,6 +136,36 @@ pub enum Weekday {
     Wednesday,
     Thursday,
     Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    // Every day of the week, Monday first
+    pub fn all() -> Vec<Weekday> {
+        vec![
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+            Weekday::Saturday,
+            Weekday::Sunday,
+        ]
+    }
+
+    // Position in the week, Monday being 0
+    pub fn days_from_monday(&self) -> usize {
+        match self {
+            Weekday::Monday => 0,
+            Weekday::Tuesday => 1,
+            Weekday::Wednesday => 2,
+            Weekday::Thursday => 3,
+            Weekday::Friday => 4,
+            Weekday::Saturday => 5,
+            Weekday::Sunday => 6,
+        }
+    }
 }
 
 impl fmt::Display for Weekday {
@@ -94,6 +176,8 @@ impl fmt::Display for Weekday {
             Weekday::Wednesday => write!(f, "Wednesday"),
             Weekday::Thursday => write!(f, "Thursday"),
             Weekday::Friday => write!(f, "Friday"),
+            Weekday::Saturday => write!(f, "Saturday"),
+            Weekday::Sunday => write!(f, "Sunday"),
         }
     }
 }
@@ -101,7 +185,21 @@ impl fmt::Display for Weekday {
 // type definitions for scheduler logic
 pub type MonthlySchedule = HashMap<Weekday, Vec<Employee>>;
 pub type DayCount = HashMap<Weekday, usize>;
+// Each employee's office days in past months, newest month first
 pub type PastSchedules = HashMap<usize, Vec<HashSet<Weekday>>>;
+pub type EmployeeId = usize;
+// Who is in the office on each working date of a month
+pub type DatedSchedule = BTreeMap<NaiveDate, Vec<EmployeeId>>;
+// Office days each employee had on each weekday over a period, from the `day_ledger` table
+pub type DayLedger = HashMap<EmployeeId, HashMap<Weekday, usize>>;
+// Desk each employee sits at on each day of a week; anyone missing had no free desk
+pub type SeatPlan = HashMap<Weekday, HashMap<EmployeeId, Desk>>;
+// Desk each employee sits at on each office date of a month
+pub type DatedSeatPlan = BTreeMap<NaiveDate, HashMap<EmployeeId, Desk>>;
+// Parking days asked for and given per employee over a period, from the `parking_ledger` table
+pub type ParkingLedger = HashMap<EmployeeId, ParkingTally>;
+// Who parks and who misses out on each office date of a month
+pub type ParkingPlan = BTreeMap<NaiveDate, ParkingDay>;
 
 // Day combinations for different required office days
 #[derive(Debug, Clone)]
@@ -115,81 +213,1191 @@ impl DayCombination {
     }
 }
 
+// User-tunable scheduler settings, persisted as JSON in the `scheduler_config` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    // Headcount limit for every weekday without its own entry (None = unlimited)
+    pub default_capacity: Option<usize>,
+    pub day_capacities: HashMap<Weekday, usize>,
+    // Weight of the per-day male/female balance term (0 = ignore sex)
+    pub sex_balance_weight: f64,
+    pub nsp_policy: NspPolicy,
+    pub solver_mode: SolverMode,
+    // Search time limit for the exact solver, shared by every week and option of one request
+    pub solver_time_budget_secs: u64,
+    // Improvement attempts made after the greedy pass (0 = skip local search)
+    pub local_search_iterations: usize,
+    // How strongly repeating last months' days is penalised
+    pub repetition_weight: f64,
+    // Months of history loaded and scored
+    pub lookback_months: usize,
+    // Weight lost by the oldest month in the window (0 = all months count the same)
+    pub recency_decay: f64,
+    // Cost of each office day outside an employee's preferred days
+    pub preference_weight: f64,
+    // Cost of each shared day an employee misses with their teammates, on average
+    pub colocation_weight: f64,
+    // Schedule each week of the month separately, with earlier weeks counted as history
+    pub weekly_rotation: bool,
+    // Move office days lost to a closure onto another open day of the same week
+    pub make_up_closed_days: bool,
+    // Cost of each office day on a weekday someone has had more than their share of
+    pub fairness_weight: f64,
+    // Months of saved schedules the fairness ledger covers
+    pub fairness_months: usize,
+    // Days the office is open, in any order; see `work_week`
+    pub work_days: Vec<Weekday>,
+    // Parking spaces shared out each office day among people who need one
+    pub parking_spaces: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            default_capacity: None,
+            day_capacities: HashMap::new(),
+            sex_balance_weight: 0.0,
+            nsp_policy: NspPolicy::default(),
+            solver_mode: SolverMode::Greedy,
+            solver_time_budget_secs: 10,
+            local_search_iterations: 500,
+            repetition_weight: 3.0,
+            lookback_months: 2,
+            recency_decay: 0.75,
+            preference_weight: 2.0,
+            colocation_weight: 5.0,
+            weekly_rotation: false,
+            make_up_closed_days: true,
+            fairness_weight: 1.0,
+            fairness_months: 12,
+            work_days: vec![
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ],
+            parking_spaces: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub enum SolverMode {
+    // Single fast pass, one employee at a time
+    #[default]
+    Greedy,
+    // Branch-and-bound over every assignment, seeded with the greedy result
+    Exact,
+}
+
+impl fmt::Display for SolverMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverMode::Greedy => write!(f, "Greedy (fast)"),
+            SolverMode::Exact => write!(f, "Exact (optimal)"),
+        }
+    }
+}
+
+// Rules for national-service personnel, applied on top of the regular settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct NspPolicy {
+    // Minimum office days for every NSP; raises their own required_days when higher
+    pub required_days: Option<u8>,
+    // Most NSPs allowed in the office on one day (None = unlimited)
+    pub max_per_day: Option<usize>,
+    // Each NSP must share at least one day with a non-NSP colleague in the same role
+    pub require_colleague_overlap: bool,
+}
+
+impl NspPolicy {
+    pub fn required_days_for(&self, employee: &Employee) -> usize {
+        match self.required_days {
+            Some(days) if employee.is_nsp => employee.required_days.max(days) as usize,
+            _ => employee.required_days as usize,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn capacity_for(&self, day: &Weekday) -> Option<usize> {
+        self.day_capacities
+            .get(day)
+            .copied()
+            .or(self.default_capacity)
+    }
+
+    // Working days in week order
+    pub fn work_week(&self) -> Vec<Weekday> {
+        Weekday::all()
+            .into_iter()
+            .filter(|day| self.work_days.contains(day))
+            .collect()
+    }
+}
+
+// Per-day headcount rule for one role, stored in the `coverage_rules` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageRule {
+    pub id: usize,
+    pub role: Role,
+    pub min_count: Option<usize>,
+    pub max_count: Option<usize>,
+    pub days: Vec<Weekday>, // empty = every day
+}
+
+impl CoverageRule {
+    pub fn applies_to(&self, day: &Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(day)
+    }
+}
+
+impl fmt::Display for CoverageRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = if self.days.is_empty() {
+            "every day".to_string()
+        } else {
+            self.days
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match (self.min_count, self.max_count) {
+            (Some(min), Some(max)) => write!(f, "{}-{} {} on {}", min, max, self.role, days),
+            (Some(min), None) => write!(f, "At least {} {} on {}", min, self.role, days),
+            (None, Some(max)) => write!(f, "At most {} {} on {}", max, self.role, days),
+            (None, None) => write!(f, "No limit for {} on {}", self.role, days),
+        }
+    }
+}
+
+// Group of employees who should be in the office together, stored in the `teams` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    pub id: usize,
+    pub name: String,
+    // Days per week every two teammates should share (capped by whoever comes in less)
+    pub colocation_days: usize,
+}
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (together {} day{} a week)",
+            self.name,
+            self.colocation_days,
+            if self.colocation_days == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Office site with its own desks, stored in the `locations` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub id: usize,
+    pub name: String,
+    // Headcount limit for every day, on top of the company-wide limits (None = unlimited)
+    pub capacity: Option<usize>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.capacity {
+            Some(capacity) => write!(f, "{} ({} desks)", self.name, capacity),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum DeskAttribute {
+    StandingDesk,
+    DualMonitors,
+    Accessible,
+}
+
+impl fmt::Display for DeskAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeskAttribute::StandingDesk => write!(f, "Standing desk"),
+            DeskAttribute::DualMonitors => write!(f, "Dual monitors"),
+            DeskAttribute::Accessible => write!(f, "Accessible"),
+        }
+    }
+}
+
+impl DeskAttribute {
+    pub fn values() -> &'static [DeskAttribute] {
+        &[
+            DeskAttribute::StandingDesk,
+            DeskAttribute::DualMonitors,
+            DeskAttribute::Accessible,
+        ]
+    }
+}
+
+// Bookable desk, stored in the `desks` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Desk {
+    pub id: usize,
+    pub name: String,
+    pub floor: String,
+    pub zone: String,
+    // None = a desk anyone can use, whatever their location
+    pub location_id: Option<usize>,
+    pub attributes: Vec<DeskAttribute>,
+}
+
+impl fmt::Display for Desk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (floor {}, zone {})",
+            self.name, self.floor, self.zone
+        )?;
+        if !self.attributes.is_empty() {
+            let attributes: Vec<String> = self.attributes.iter().map(|a| a.to_string()).collect();
+            write!(f, " - {}", attributes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ParkingTally {
+    // Office days the employee needed a space
+    pub requested: usize,
+    // Office days they got one
+    pub parked: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParkingDay {
+    pub parked: Vec<Employee>,
+    // Needed a space but none was left
+    pub missed: Vec<Employee>,
+}
+
+// Meeting room, stored in the `meeting_rooms` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetingRoom {
+    pub id: usize,
+    pub name: String,
+    pub capacity: usize,
+    // None = not tied to one office
+    pub location_id: Option<usize>,
+}
+
+impl fmt::Display for MeetingRoom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (seats {})", self.name, self.capacity)
+    }
+}
+
+// Meeting in a room on one date, stored in the `bookings` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Booking {
+    pub id: usize,
+    pub room_id: usize,
+    pub title: String,
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub attendees: Vec<EmployeeId>,
+}
+
+impl Booking {
+    // Same room, same date and the time slots overlap; back-to-back meetings do not clash
+    pub fn clashes_with(&self, other: &Booking) -> bool {
+        self.room_id == other.room_id
+            && self.date == other.date
+            && self.start < other.end
+            && other.start < self.end
+    }
+}
+
+impl fmt::Display for Booking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}-{} {}",
+            self.date.format("%a %-d %b"),
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            self.title
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PairKind {
+    // In the office together on at least this many days (capped by whoever comes in less)
+    Together { min_days: usize },
+    // Never in the office on the same day
+    Apart,
+}
+
+// Rule between two employees, stored in the `pair_constraints` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairConstraint {
+    pub id: usize,
+    pub first_id: usize,
+    pub second_id: usize,
+    pub kind: PairKind,
+    // Filled in from the employees table when loaded, for display
+    pub first_name: String,
+    pub second_name: String,
+}
+
+impl PairConstraint {
+    pub fn involves(&self, employee_id: usize) -> bool {
+        self.first_id == employee_id || self.second_id == employee_id
+    }
+
+    pub fn partner_of(&self, employee_id: usize) -> usize {
+        if self.first_id == employee_id {
+            self.second_id
+        } else {
+            self.first_id
+        }
+    }
+}
+
+impl fmt::Display for PairConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PairKind::Together { min_days } => write!(
+                f,
+                "{} and {} together on at least {} day(s)",
+                self.first_name, self.second_name, min_days
+            ),
+            PairKind::Apart => write!(
+                f,
+                "{} and {} never on the same day",
+                self.first_name, self.second_name
+            ),
+        }
+    }
+}
+
+// Date the office is shut, such as a public holiday, stored in the `closures` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    pub id: usize,
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+impl fmt::Display for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.date.format("%a %-d %b %Y"), self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeaveKind {
+    Annual,
+    Sick,
+    Training,
+}
+
+impl fmt::Display for LeaveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeaveKind::Annual => write!(f, "Annual"),
+            LeaveKind::Sick => write!(f, "Sick"),
+            LeaveKind::Training => write!(f, "Training"),
+        }
+    }
+}
+
+impl LeaveKind {
+    pub fn values() -> &'static [LeaveKind] {
+        &[LeaveKind::Annual, LeaveKind::Sick, LeaveKind::Training]
+    }
+}
+
+// Period an employee is away, both dates included, stored in the `leave` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leave {
+    pub id: usize,
+    pub employee_id: usize,
+    pub kind: LeaveKind,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Leave {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+impl fmt::Display for Leave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(
+                f,
+                "{} leave on {}",
+                self.kind,
+                self.start.format("%a %-d %b %Y")
+            )
+        } else {
+            write!(
+                f,
+                "{} leave from {} to {}",
+                self.kind,
+                self.start.format("%a %-d %b %Y"),
+                self.end.format("%a %-d %b %Y")
+            )
+        }
+    }
+}
+
+// Everything besides the employees themselves that restricts where people can go
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleConstraints {
+    pub coverage_rules: Vec<CoverageRule>,
+    pub teams: Vec<Team>,
+    pub pairs: Vec<PairConstraint>,
+    pub locations: Vec<Location>,
+    pub closures: Vec<Closure>,
+    pub leave: Vec<Leave>,
+    // Days locked by hand on the Schedules page, by employee id (not stored)
+    pub pins: HashMap<usize, Vec<Weekday>>,
+    // Weekdays of the week being scheduled that fall on a closure (not stored)
+    pub closed_days: Vec<Weekday>,
+    // Weekdays each employee is on leave in the week being scheduled, by employee id;
+    // every weekday when the whole week is leave (not stored)
+    pub leave_days: HashMap<usize, Vec<Weekday>>,
+    // Office days each employee owes in the week being scheduled when closures or leave
+    // change it, by employee id; takes the place of the NSP policy (not stored)
+    pub week_required_days: HashMap<usize, usize>,
+    // When the exact solver must stop searching; None gives it the whole time budget from
+    // the moment it starts (not stored)
+    pub solver_deadline: Option<Instant>,
+    // Weekday totals over the fairness window, loaded for each run
+    pub ledger: DayLedger,
+}
+
 pub struct ScheduleGenerator {
     pub weekdays: Vec<Weekday>,
     pub day_combinations: HashMap<usize, Vec<DayCombination>>,
+    // Days each employee had on each weekday beyond their share, by employee id
+    pub day_excess: HashMap<usize, HashMap<Weekday, f64>>,
+    pub config: SchedulerConfig,
+    pub constraints: ScheduleConstraints,
 }
 
 impl ScheduleGenerator {
-    pub fn new() -> Self {
-        let weekdays = vec![
-            Weekday::Monday,
-            Weekday::Tuesday,
-            Weekday::Wednesday,
-            Weekday::Thursday,
-            Weekday::Friday,
-        ];
+    pub fn new(config: SchedulerConfig, constraints: ScheduleConstraints) -> Self {
+        let weekdays: Vec<Weekday> = config
+            .work_week()
+            .into_iter()
+            .filter(|day| !constraints.closed_days.contains(day))
+            .collect();
 
-        let day_combinations = Self::initialize_day_combinations();
+        let day_combinations = Self::initialize_day_combinations(&weekdays);
+        let day_excess = Self::ledger_excess(&constraints.ledger);
 
         Self {
             weekdays,
             day_combinations,
+            day_excess,
+            config,
+            constraints,
+        }
+    }
+
+    // Compares each employee's weekdays with the company-wide split of office days. Someone
+    // with 40 office days where a fifth of all office days fall on Monday is expected to have
+    // had 8 Mondays; with 11 their Monday excess is 3. Being owed a day counts as no excess,
+    // which keeps every scoring term non-negative for the exact solver's bounds.
+    fn ledger_excess(ledger: &DayLedger) -> HashMap<usize, HashMap<Weekday, f64>> {
+        let mut day_totals: HashMap<&Weekday, usize> = HashMap::new();
+        for counts in ledger.values() {
+            for (day, count) in counts {
+                *day_totals.entry(day).or_insert(0) += count;
+            }
         }
+        let all_days = day_totals.values().sum::<usize>();
+        if all_days == 0 {
+            return HashMap::new();
+        }
+
+        ledger
+            .iter()
+            .map(|(id, counts)| {
+                let own_days = counts.values().sum::<usize>() as f64;
+                let excess = day_totals
+                    .iter()
+                    .map(|(day, total)| {
+                        let expected = own_days * *total as f64 / all_days as f64;
+                        let had = counts.get(*day).copied().unwrap_or(0) as f64;
+                        ((*day).clone(), (had - expected).max(0.0))
+                    })
+                    .collect();
+                (*id, excess)
+            })
+            .collect()
     }
 
-    fn initialize_day_combinations() -> HashMap<usize, Vec<DayCombination>> {
+    // Builds the combinations for every required-days count from 0 up to the length of the week.
+    // Combinations without back-to-back days are preferred so office days stay spread out;
+    // counts that have none of those (e.g. 4 out of 5 days) fall back to every combination.
+    // Days are back-to-back by the calendar, so Monday and Wednesday count as spread even
+    // when Tuesday is not a working day.
+    fn initialize_day_combinations(weekdays: &[Weekday]) -> HashMap<usize, Vec<DayCombination>> {
         let mut combinations = HashMap::new();
 
-        // 1-day combinations
-        combinations.insert(
-            1,
-            vec![
-                DayCombination::new(vec![Weekday::Monday]),
-                DayCombination::new(vec![Weekday::Tuesday]),
-                DayCombination::new(vec![Weekday::Wednesday]),
-                DayCombination::new(vec![Weekday::Thursday]),
-                DayCombination::new(vec![Weekday::Friday]),
-            ],
-        );
-
-        // 2-day combinations
-        combinations.insert(
-            2,
-            vec![
-                DayCombination::new(vec![Weekday::Monday, Weekday::Wednesday]),
-                DayCombination::new(vec![Weekday::Monday, Weekday::Thursday]),
-                DayCombination::new(vec![Weekday::Monday, Weekday::Friday]),
-                DayCombination::new(vec![Weekday::Tuesday, Weekday::Thursday]),
-                DayCombination::new(vec![Weekday::Tuesday, Weekday::Friday]),
-                DayCombination::new(vec![Weekday::Wednesday, Weekday::Friday]),
-            ],
-        );
+        for num_days in 0..=weekdays.len() {
+            let all_combos = index_combinations(weekdays.len(), num_days);
 
-        // 3-day combinations
-        combinations.insert(
-            3,
-            vec![DayCombination::new(vec![
-                Weekday::Monday,
-                Weekday::Wednesday,
-                Weekday::Friday,
-            ])],
-        );
+            let spread_combos: Vec<Vec<usize>> = all_combos
+                .iter()
+                .filter(|indices| {
+                    indices.windows(2).all(|pair| {
+                        weekdays[pair[1]].days_from_monday() - weekdays[pair[0]].days_from_monday()
+                            > 1
+                    })
+                })
+                .cloned()
+                .collect();
 
-        // 5-day combinations
-        combinations.insert(
-            5,
-            vec![DayCombination::new(vec![
-                Weekday::Monday,
-                Weekday::Tuesday,
-                Weekday::Wednesday,
-                Weekday::Thursday,
-                Weekday::Friday,
-            ])],
-        );
+            let chosen = if spread_combos.is_empty() {
+                all_combos
+            } else {
+                spread_combos
+            };
+
+            combinations.insert(
+                num_days,
+                chosen
+                    .into_iter()
+                    .map(|indices| {
+                        DayCombination::new(indices.iter().map(|&i| weekdays[i].clone()).collect())
+                    })
+                    .collect(),
+            );
+        }
 
         combinations
     }
+
+    // Office days the employee needs this week: the week's own target when closures or leave
+    // changed it, otherwise their required days as raised by the NSP policy
+    pub fn required_days_for(&self, employee: &Employee) -> usize {
+        self.constraints
+            .week_required_days
+            .get(&employee.id)
+            .copied()
+            .unwrap_or_else(|| self.config.nsp_policy.required_days_for(employee))
+    }
+
+    pub fn pinned_days(&self, employee_id: usize) -> &[Weekday] {
+        self.constraints
+            .pins
+            .get(&employee_id)
+            .map_or(&[], |days| days.as_slice())
+    }
+
+    // Combinations of `size` days that keep every pinned day. The usual combinations are
+    // used when one of them does, otherwise any combination of that size that does.
+    pub fn combinations_with(&self, size: usize, pinned: &[Weekday]) -> Vec<DayCombination> {
+        let keeps_pins = |days: &[Weekday]| pinned.iter().all(|day| days.contains(day));
+        let usual: Vec<DayCombination> = self
+            .day_combinations
+            .get(&size)
+            .into_iter()
+            .flatten()
+            .filter(|combo| keeps_pins(&combo.days))
+            .cloned()
+            .collect();
+        if !usual.is_empty() || pinned.is_empty() {
+            return usual;
+        }
+
+        index_combinations(self.weekdays.len(), size)
+            .into_iter()
+            .map(|indices| {
+                DayCombination::new(indices.iter().map(|&i| self.weekdays[i].clone()).collect())
+            })
+            .filter(|combo| keeps_pins(&combo.days))
+            .collect()
+    }
+}
+
+// All ascending index sets of size `k` taken from `0..n`
+fn index_combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    fn build(
+        start: usize,
+        n: usize,
+        k: usize,
+        current: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if current.len() == k {
+            out.push(current.clone());
+            return;
+        }
+        for i in start..n {
+            current.push(i);
+            build(i + 1, n, k, current, out);
+            current.pop();
+        }
+    }
+
+    let mut out = Vec::new();
+    if k <= n {
+        build(0, n, k, &mut Vec::new(), &mut out);
+    }
+    out
+}
+
+// Result of a generation run: the schedule plus anything the scheduler could not satisfy
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScheduleResult {
+    pub schedule: MonthlySchedule,
+    pub warnings: Vec<ScheduleWarning>,
+    // Set by the exact solver: whether the search finished within its time budget
+    pub proven_optimal: Option<bool>,
+    // RNG seed the run used; passing it back reproduces the schedule
+    pub seed: u64,
+    // Why each employee (by id) got their days
+    pub explanations: HashMap<usize, EmployeeExplanation>,
+}
+
+// One calendar week of a month. Weeks at either end of the month can be partial, in which
+// case the schedule only holds the weekdays that fall inside the month.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScheduleWeek {
+    // Working dates of the week inside the month, in order
+    pub dates: Vec<NaiveDate>,
+    pub result: ScheduleResult,
+}
+
+// One of several month schedules generated side by side, with the figures used to compare them
+#[derive(Debug, Clone)]
+pub struct ScheduleOption {
+    pub weeks: Vec<ScheduleWeek>,
+    // Office days per weekday, summed over the month
+    pub day_counts: Vec<(Weekday, usize)>,
+    // Daily headcount variance and repetition score, summed over the weeks
+    pub variance: f64,
+    pub repetition: f64,
+    // Coverage, NSP, pair, team and capacity rules broken; the same breach in several weeks
+    // counts once
+    pub breaches: usize,
+    // Employees left unplaced in at least one week
+    pub unplaced: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Placement {
+    Fixed,
+    Pinned,
+    Flexible,
+    Remote,
+    OnLeave,
+    Unplaced,
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placement::Fixed => write!(f, "Fixed days"),
+            Placement::Pinned => write!(f, "Pinned on the schedule"),
+            Placement::Flexible => write!(f, "Flexible"),
+            Placement::Remote => write!(f, "Fully remote"),
+            Placement::OnLeave => write!(f, "On leave all week"),
+            Placement::Unplaced => write!(f, "Not placed"),
+        }
+    }
+}
+
+// One combination the greedy pass scored for an employee. Terms are already weighted,
+// so variance + repetition + fairness + coverage + sex_balance + preference + colocation
+// = total (lower is better).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombinationScore {
+    pub days: Vec<Weekday>,
+    pub variance: f64,
+    pub repetition: f64,
+    pub fairness: f64,
+    pub coverage: f64,
+    pub sex_balance: f64,
+    pub preference: f64,
+    pub colocation: f64,
+    pub total: f64,
+    // The employee ended up on exactly these days
+    pub chosen: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmployeeExplanation {
+    pub placement: Placement,
+    // Best first; empty for fixed, remote and unplaced employees
+    pub considered: Vec<CombinationScore>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleWarning {
+    // `days` is the number of office days the scheduler tried to place
+    UnplacedEmployee {
+        employee: Employee,
+        days: usize,
+    },
+    NoAvailableCombination {
+        employee: Employee,
+        days: usize,
+    },
+    NoCapacityLeft {
+        employee: Employee,
+        days: usize,
+    },
+    DayOverCapacity {
+        day: Weekday,
+        count: usize,
+        capacity: usize,
+    },
+    LocationOverCapacity {
+        location: String,
+        day: Weekday,
+        count: usize,
+        capacity: usize,
+    },
+    CoverageBelowMinimum {
+        day: Weekday,
+        role: Role,
+        count: usize,
+        min: usize,
+    },
+    CoverageAboveMaximum {
+        day: Weekday,
+        role: Role,
+        count: usize,
+        max: usize,
+    },
+    NspCapExceeded {
+        day: Weekday,
+        count: usize,
+        max: usize,
+    },
+    NspWithoutColleague(Employee),
+    PairTogetherShort {
+        first: String,
+        second: String,
+        shared: usize,
+        required: usize,
+    },
+    PairApartOverlap {
+        first: String,
+        second: String,
+        shared: usize,
+    },
+    TeamBelowColocationTarget {
+        team: String,
+        pairs_short: usize,
+        pairs: usize,
+        target: usize,
+    },
+}
+
+impl fmt::Display for ScheduleWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleWarning::UnplacedEmployee { employee, days } => write!(
+                f,
+                "{} could not be placed: no valid {}-day combination in the work week",
+                employee.name, days
+            ),
+            ScheduleWarning::NoAvailableCombination { employee, days } => write!(
+                f,
+                "{} could not be placed: every {}-day combination includes one of their unavailable days",
+                employee.name, days
+            ),
+            ScheduleWarning::NoCapacityLeft { employee, days } => write!(
+                f,
+                "{} could not be placed: every {}-day combination exceeds a daily capacity limit",
+                employee.name, days
+            ),
+            ScheduleWarning::DayOverCapacity {
+                day,
+                count,
+                capacity,
+            } => write!(
+                f,
+                "{} is over capacity: {} fixed-day employees for {} desks",
+                day, count, capacity
+            ),
+            ScheduleWarning::LocationOverCapacity {
+                location,
+                day,
+                count,
+                capacity,
+            } => write!(
+                f,
+                "{} is over capacity on {}: {} fixed-day employees for {} desks",
+                location, day, count, capacity
+            ),
+            ScheduleWarning::CoverageBelowMinimum {
+                day,
+                role,
+                count,
+                min,
+            } => write!(
+                f,
+                "{}: only {} {} in the office, at least {} required",
+                day, count, role, min
+            ),
+            ScheduleWarning::CoverageAboveMaximum {
+                day,
+                role,
+                count,
+                max,
+            } => write!(
+                f,
+                "{}: {} {} in the office, at most {} allowed",
+                day, count, role, max
+            ),
+            ScheduleWarning::NspCapExceeded { day, count, max } => write!(
+                f,
+                "{}: {} NSPs in the office, at most {} allowed",
+                day, count, max
+            ),
+            ScheduleWarning::NspWithoutColleague(employee) => write!(
+                f,
+                "{} (NSP) shares no office day with a non-NSP {}",
+                employee.name, employee.role
+            ),
+            ScheduleWarning::PairTogetherShort {
+                first,
+                second,
+                shared,
+                required,
+            } => write!(
+                f,
+                "{} and {} share {} office day(s) but must share {}",
+                first, second, shared, required
+            ),
+            ScheduleWarning::PairApartOverlap {
+                first,
+                second,
+                shared,
+            } => write!(
+                f,
+                "{} and {} must not overlap but share {} office day(s)",
+                first, second, shared
+            ),
+            ScheduleWarning::TeamBelowColocationTarget {
+                team,
+                pairs_short,
+                pairs,
+                target,
+            } => write!(
+                f,
+                "Team {}: {} of {} teammate pairs share fewer than {} office day(s)",
+                team, pairs_short, pairs, target
+            ),
+        }
+    }
+}
+
+// Problem with a meeting booking, checked against who the schedule puts in the office
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookingWarning {
+    NoOfficeDay {
+        booking: String,
+        date: NaiveDate,
+    },
+    AttendeeNotInOffice {
+        booking: String,
+        employee: String,
+        date: NaiveDate,
+    },
+    AttendeeAtOtherOffice {
+        booking: String,
+        employee: String,
+        room: String,
+    },
+    RoomOverCapacity {
+        booking: String,
+        room: String,
+        attendees: usize,
+        capacity: usize,
+    },
+    RoomDoubleBooked {
+        room: String,
+        first: String,
+        second: String,
+    },
+}
+
+impl fmt::Display for BookingWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookingWarning::NoOfficeDay { booking, date } => write!(
+                f,
+                "{}: nobody is scheduled in the office on {}",
+                booking,
+                date.format("%a %-d %b")
+            ),
+            BookingWarning::AttendeeNotInOffice {
+                booking,
+                employee,
+                date,
+            } => write!(
+                f,
+                "{}: {} is not in the office on {}",
+                booking,
+                employee,
+                date.format("%a %-d %b")
+            ),
+            BookingWarning::AttendeeAtOtherOffice {
+                booking,
+                employee,
+                room,
+            } => write!(
+                f,
+                "{}: {} works from a different office than {}",
+                booking, employee, room
+            ),
+            BookingWarning::RoomOverCapacity {
+                booking,
+                room,
+                attendees,
+                capacity,
+            } => write!(
+                f,
+                "{}: {} attendees but {} seats {}",
+                booking, attendees, room, capacity
+            ),
+            BookingWarning::RoomDoubleBooked {
+                room,
+                first,
+                second,
+            } => write!(f, "{} is double booked: {} and {}", room, first, second),
+        }
+    }
+}
+
+// Office date ranked for a meeting by how many of the invitees are in
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetingDay {
+    pub date: NaiveDate,
+    pub present: usize,
+    // Names of the invitees who are not in that day
+    pub missing: Vec<String>,
+}
+
+// Problem in the inputs found before generation, naming the employees involved
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeasibilityIssue {
+    FixedDaysMismatch {
+        employee: String,
+        fixed: usize,
+        required: usize,
+    },
+    NoDayCombinations {
+        required_days: usize,
+        employees: Vec<String>,
+    },
+    FixedDaysOverCapacity {
+        day: Weekday,
+        capacity: usize,
+        employees: Vec<String>,
+    },
+    FixedOnUnavailableDay {
+        employee: String,
+        days: Vec<Weekday>,
+    },
+    NoAvailableCombination {
+        employee: String,
+        required_days: usize,
+    },
+    CoverageRuleUnreachable {
+        rule: String,
+        employees: Vec<String>,
+    },
+    NspCapTooLow {
+        max_per_day: usize,
+        office_days: usize,
+        employees: Vec<String>,
+    },
+    LocationCapacityTooLow {
+        location: String,
+        capacity: usize,
+        office_days: usize,
+        employees: Vec<String>,
+    },
+    ConflictingPairRules {
+        first: String,
+        second: String,
+    },
+    PairUnsatisfiable {
+        rule: String,
+    },
+    PinOnUnavailableDay {
+        employee: String,
+        dates: Vec<NaiveDate>,
+    },
+    PinOnLeave {
+        employee: String,
+        dates: Vec<NaiveDate>,
+    },
+    PinOnClosure {
+        employee: String,
+        dates: Vec<NaiveDate>,
+    },
+    PinnedDayOverCapacity {
+        date: NaiveDate,
+        capacity: usize,
+        employees: Vec<String>,
+    },
+}
+
+fn names(employees: &[String]) -> String {
+    if employees.is_empty() {
+        "nobody".to_string()
+    } else {
+        employees.join(", ")
+    }
+}
+
+fn date_list(dates: &[NaiveDate]) -> String {
+    dates
+        .iter()
+        .map(|date| date.format("%a %-d %b").to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for FeasibilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeasibilityIssue::FixedDaysMismatch {
+                employee,
+                fixed,
+                required,
+            } => write!(
+                f,
+                "{} has {} fixed day(s) but {} required day(s)",
+                employee, fixed, required
+            ),
+            FeasibilityIssue::NoDayCombinations {
+                required_days,
+                employees,
+            } => write!(
+                f,
+                "No {}-day combination exists in the work week for: {}",
+                required_days,
+                names(employees)
+            ),
+            FeasibilityIssue::FixedDaysOverCapacity {
+                day,
+                capacity,
+                employees,
+            } => write!(
+                f,
+                "{} has {} desks but {} fixed-day employees: {}",
+                day,
+                capacity,
+                employees.len(),
+                names(employees)
+            ),
+            FeasibilityIssue::FixedOnUnavailableDay { employee, days } => write!(
+                f,
+                "{} has fixed days they are marked unavailable on: {}",
+                employee,
+                days.iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            FeasibilityIssue::NoAvailableCombination {
+                employee,
+                required_days,
+            } => write!(
+                f,
+                "{} needs {} office day(s) but every combination includes an unavailable day",
+                employee, required_days
+            ),
+            FeasibilityIssue::CoverageRuleUnreachable { rule, employees } => write!(
+                f,
+                "Coverage rule \"{}\" cannot be met: only {} employee(s) with that role come in ({})",
+                rule,
+                employees.len(),
+                names(employees)
+            ),
+            FeasibilityIssue::NspCapTooLow {
+                max_per_day,
+                office_days,
+                employees,
+            } => write!(
+                f,
+                "NSPs need {} office days a week but at most {} per day are allowed: {}",
+                office_days,
+                max_per_day,
+                names(employees)
+            ),
+            FeasibilityIssue::LocationCapacityTooLow {
+                location,
+                capacity,
+                office_days,
+                employees,
+            } => write!(
+                f,
+                "{} has {} desks a day but its staff need {} office days a week: {}",
+                location,
+                capacity,
+                office_days,
+                names(employees)
+            ),
+            FeasibilityIssue::ConflictingPairRules { first, second } => write!(
+                f,
+                "{} and {} are required to be both together and apart",
+                first, second
+            ),
+            FeasibilityIssue::PairUnsatisfiable { rule } => {
+                write!(f, "Pair rule \"{}\" cannot be met with their office days", rule)
+            }
+            FeasibilityIssue::PinOnUnavailableDay { employee, dates } => write!(
+                f,
+                "{} is pinned on days they are marked unavailable: {}",
+                employee,
+                date_list(dates)
+            ),
+            FeasibilityIssue::PinOnLeave { employee, dates } => write!(
+                f,
+                "{} is pinned on days they are on leave: {}",
+                employee,
+                date_list(dates)
+            ),
+            FeasibilityIssue::PinOnClosure { employee, dates } => write!(
+                f,
+                "{} is pinned on days the office is closed: {}",
+                employee,
+                date_list(dates)
+            ),
+            FeasibilityIssue::PinnedDayOverCapacity {
+                date,
+                capacity,
+                employees,
+            } => write!(
+                f,
+                "{} has {} desks but {} people are pinned or fixed on it: {}",
+                date.format("%a %-d %b"),
+                capacity,
+                employees.len(),
+                names(employees)
+            ),
+        }
+    }
 }
 
 // #[derive(Debug, Clone)]
@@ -0,0 +1,169 @@
+use crate::server::calendar::weekday_of;
+use crate::server::schema::{
+    DatedSeatPlan, Desk, Employee, EmployeeId, MonthlySchedule, ScheduleWeek, SeatPlan, Weekday,
+};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Desks at the office the employee comes in to, plus desks anyone can use
+fn desks_for<'a>(desks: &'a [Desk], employee: &Employee) -> Vec<(usize, &'a Desk)> {
+    desks
+        .iter()
+        .enumerate()
+        .filter(|(_, desk)| {
+            desk.location_id.is_none()
+                || employee.office_location().is_none()
+                || desk.location_id == employee.office_location()
+        })
+        .collect()
+}
+
+fn meets_needs(desk: &Desk, employee: &Employee) -> bool {
+    employee
+        .desk_needs
+        .iter()
+        .all(|need| desk.attributes.contains(need))
+}
+
+// Teammates already seated on the desk's floor, and in its zone
+fn teammates_near(
+    team_seats: &HashMap<usize, Vec<(String, String)>>,
+    employee: &Employee,
+    desk: &Desk,
+) -> (usize, usize) {
+    let Some(seats) = employee.team_id.and_then(|team| team_seats.get(&team)) else {
+        return (0, 0);
+    };
+    let in_zone = seats
+        .iter()
+        .filter(|(floor, zone)| *floor == desk.floor && *zone == desk.zone)
+        .count();
+    let on_floor = seats
+        .iter()
+        .filter(|(floor, _)| *floor == desk.floor)
+        .count();
+    (in_zone, on_floor)
+}
+
+// Gives everyone in the week's schedule a desk on each of their office days. Each person gets
+// one home desk for the week: a desk with the equipment they need always wins, then the desk
+// free on most of their days, then desks in the zone (or on the floor) where their teammates
+// already sit. Days their desk is taken fall back to the best other free desk. Larger teams
+// are seated first so they can fill a zone together. The result only depends on its inputs,
+// so a saved schedule gets the same desks every time it is opened.
+pub fn assign_desks(schedule: &MonthlySchedule, weekdays: &[Weekday], desks: &[Desk]) -> SeatPlan {
+    let mut plan: SeatPlan = weekdays
+        .iter()
+        .map(|day| (day.clone(), HashMap::new()))
+        .collect();
+    if desks.is_empty() {
+        return plan;
+    }
+
+    let mut employee_days: BTreeMap<EmployeeId, (&Employee, Vec<Weekday>)> = BTreeMap::new();
+    for day in weekdays {
+        for employee in schedule.get(day).into_iter().flatten() {
+            employee_days
+                .entry(employee.id)
+                .or_insert((employee, Vec::new()))
+                .1
+                .push(day.clone());
+        }
+    }
+    let mut team_sizes: HashMap<usize, usize> = HashMap::new();
+    for (employee, _) in employee_days.values() {
+        if let Some(team) = employee.team_id {
+            *team_sizes.entry(team).or_insert(0) += 1;
+        }
+    }
+    let mut order: Vec<(&Employee, Vec<Weekday>)> = employee_days.into_values().collect();
+    order.sort_by_key(|(employee, days)| {
+        let team_size = employee.team_id.map_or(0, |team| team_sizes[&team]);
+        (
+            Reverse(team_size),
+            employee.team_id,
+            Reverse(days.len()),
+            employee.id,
+        )
+    });
+
+    let mut taken: HashMap<Weekday, HashSet<usize>> = HashMap::new();
+    let mut team_seats: HashMap<usize, Vec<(String, String)>> = HashMap::new();
+    for (employee, days) in order {
+        let candidates = desks_for(desks, employee);
+        let is_free = |desk: &Desk, day: &Weekday| {
+            taken
+                .get(day)
+                .is_none_or(|desk_ids| !desk_ids.contains(&desk.id))
+        };
+
+        // Ties go to the desk listed first
+        let home_desk = candidates
+            .iter()
+            .map(|(index, desk)| {
+                let free_days = days.iter().filter(|day| is_free(desk, day)).count();
+                let near = teammates_near(&team_seats, employee, desk);
+                // Equipment first: an accessible desk matters more than keeping one desk all week
+                let key = (
+                    meets_needs(desk, employee),
+                    free_days,
+                    near,
+                    Reverse(*index),
+                );
+                (key, *desk)
+            })
+            .filter(|((_, free_days, ..), _)| *free_days > 0)
+            .max_by_key(|(key, _)| *key)
+            .map(|(_, desk)| desk);
+
+        let mut seats = Vec::new();
+        for day in &days {
+            let desk = match home_desk {
+                Some(desk) if is_free(desk, day) => Some(desk),
+                _ => candidates
+                    .iter()
+                    .filter(|(_, desk)| is_free(desk, day))
+                    .max_by_key(|(index, desk)| {
+                        (
+                            meets_needs(desk, employee),
+                            teammates_near(&team_seats, employee, desk),
+                            Reverse(*index),
+                        )
+                    })
+                    .map(|(_, desk)| *desk),
+            };
+            if let Some(desk) = desk {
+                seats.push((day.clone(), desk));
+            }
+        }
+
+        for (day, desk) in seats {
+            taken.entry(day.clone()).or_default().insert(desk.id);
+            if let Some(team) = employee.team_id {
+                team_seats
+                    .entry(team)
+                    .or_default()
+                    .push((desk.floor.clone(), desk.zone.clone()));
+            }
+            if let Some(day_seats) = plan.get_mut(&day) {
+                day_seats.insert(employee.id, desk.clone());
+            }
+        }
+    }
+    plan
+}
+
+// Desks for every office date of the month, seated one week at a time as the schedule shows
+pub fn assign_month_desks(weeks: &[ScheduleWeek], desks: &[Desk]) -> DatedSeatPlan {
+    let mut plan = DatedSeatPlan::new();
+    for week in weeks {
+        let weekdays: Vec<Weekday> = week.dates.iter().map(|date| weekday_of(*date)).collect();
+        let mut week_plan = assign_desks(&week.result.schedule, &weekdays, desks);
+        for date in &week.dates {
+            if let Some(day_seats) = week_plan.remove(&weekday_of(*date)) {
+                plan.insert(*date, day_seats);
+            }
+        }
+    }
+    plan
+}
@@ -0,0 +1,510 @@
+use crate::server::scheduler::{
+    check_coverage_rules, check_nsp_policy, check_pair_constraints, check_team_colocation,
+    company_female_ratio, fairness_cost, generate_schedule, is_available, location_count,
+    past_day_frequencies, process_fixed_schedules, schedule_objective,
+};
+use crate::server::schema::{
+    DayCount, Employee, MonthlySchedule, PastSchedules, ScheduleGenerator, ScheduleResult,
+    ScheduleWarning, SchedulerConfig,
+};
+use rand::rngs::StdRng;
+use std::time::{Duration, Instant};
+
+// Cost of leaving a flexible employee out because every combination is full
+const UNPLACED_PENALTY: f64 = 1000.0;
+
+// A flexible employee and every combination they could take
+struct Candidate {
+    employee: Employee,
+    // Weekday indices of each combination, with its weighted repetition and fairness cost
+    options: Vec<(Vec<usize>, f64)>,
+    required_days: usize,
+    // Index into the capped locations, when the employee's location has a limit
+    location: Option<usize>,
+}
+
+struct Search<'a> {
+    generator: &'a ScheduleGenerator,
+    past_schedules: &'a PastSchedules,
+    base_schedule: &'a MonthlySchedule,
+    female_ratio: f64,
+    candidates: Vec<Candidate>,
+    capacities: Vec<Option<usize>>,
+    location_capacities: Vec<usize>,
+    // Office days still to hand out from each depth onwards
+    days_after: Vec<usize>,
+    // Smallest possible history cost from each depth onwards
+    history_after: Vec<f64>,
+    counts: Vec<usize>,
+    nsp_counts: Vec<usize>,
+    // Headcount of each capped location on each weekday
+    location_counts: Vec<Vec<usize>>,
+    choice: Vec<Option<usize>>,
+    best_choice: Option<Vec<Option<usize>>>,
+    best_score: f64,
+    deadline: Instant,
+    nodes: u64,
+    timed_out: bool,
+}
+
+impl Search<'_> {
+    fn run(&mut self, depth: usize, history_so_far: f64) {
+        self.nodes += 1;
+        // Checked on the first node too, so a run whose share of the budget is already
+        // spent returns the greedy schedule straight away
+        if (self.nodes == 1 || self.nodes.is_multiple_of(1024)) && Instant::now() >= self.deadline {
+            self.timed_out = true;
+        }
+        if self.timed_out {
+            return;
+        }
+
+        if depth == self.candidates.len() {
+            let score = self.leaf_score();
+            if score < self.best_score {
+                self.best_score = score;
+                self.best_choice = Some(self.choice.clone());
+            }
+            return;
+        }
+
+        // Nothing below this node can beat the incumbent
+        let bound = variance_lower_bound(&self.counts, self.days_after[depth])
+            + history_so_far
+            + self.history_after[depth];
+        if bound >= self.best_score {
+            return;
+        }
+
+        // Try the cheapest-looking combinations first so good incumbents show up early
+        let candidate = &self.candidates[depth];
+        let location = candidate.location;
+        let nsp_cap = if candidate.employee.is_nsp {
+            self.generator.config.nsp_policy.max_per_day
+        } else {
+            None
+        };
+        let mut order: Vec<(usize, f64)> = candidate
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(_, (days, _))| {
+                days.iter().all(|&d| {
+                    self.capacities[d].is_none_or(|cap| self.counts[d] < cap)
+                        && nsp_cap.is_none_or(|cap| self.nsp_counts[d] < cap)
+                        && location.is_none_or(|l| {
+                            self.location_counts[l][d] < self.location_capacities[l]
+                        })
+                })
+            })
+            .map(|(i, (days, history))| {
+                let spread = days
+                    .iter()
+                    .map(|&d| (2 * self.counts[d] + 1) as f64)
+                    .sum::<f64>();
+                (i, spread + history)
+            })
+            .collect();
+        order.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        if order.is_empty() {
+            // Every combination is full: the employee stays unplaced
+            self.choice[depth] = None;
+            self.run(depth + 1, history_so_far);
+            return;
+        }
+
+        let is_nsp = candidate.employee.is_nsp;
+        for (option, _) in order {
+            let (days, history) = self.candidates[depth].options[option].clone();
+            for &d in &days {
+                self.counts[d] += 1;
+                if is_nsp {
+                    self.nsp_counts[d] += 1;
+                }
+                if let Some(l) = location {
+                    self.location_counts[l][d] += 1;
+                }
+            }
+            self.choice[depth] = Some(option);
+
+            self.run(depth + 1, history_so_far + history);
+
+            for &d in &days {
+                self.counts[d] -= 1;
+                if is_nsp {
+                    self.nsp_counts[d] -= 1;
+                }
+                if let Some(l) = location {
+                    self.location_counts[l][d] -= 1;
+                }
+            }
+            if self.timed_out {
+                break;
+            }
+        }
+        self.choice[depth] = None;
+    }
+
+    fn build_schedule(&self, choice: &[Option<usize>]) -> MonthlySchedule {
+        let mut schedule = self.base_schedule.clone();
+        for (candidate, picked) in self.candidates.iter().zip(choice) {
+            if let Some(option) = picked {
+                for &d in &candidate.options[*option].0 {
+                    if let Some(daily_schedule) = schedule.get_mut(&self.generator.weekdays[d]) {
+                        daily_schedule.push(candidate.employee.clone());
+                    }
+                }
+            }
+        }
+        schedule
+    }
+
+    fn leaf_score(&self) -> f64 {
+        let schedule = self.build_schedule(&self.choice);
+        let unplaced = self.choice.iter().filter(|c| c.is_none()).count();
+        schedule_objective(
+            self.generator,
+            &schedule,
+            self.past_schedules,
+            self.female_ratio,
+        ) + UNPLACED_PENALTY * unplaced as f64
+    }
+}
+
+// Smallest variance reachable by adding office days to `counts`, ignoring which employee
+// each day belongs to. Without skips exactly `days` are added; skipping anyone costs
+// UNPLACED_PENALTY, so that case is bounded by the best variance over any smaller amount.
+fn variance_lower_bound(counts: &[usize], days: usize) -> f64 {
+    let mut counts = counts.to_vec();
+    let mut best_with_skips = variance(&counts);
+    for _ in 0..days {
+        if let Some(smallest) = counts.iter_mut().min() {
+            *smallest += 1;
+        }
+        best_with_skips = best_with_skips.min(variance(&counts));
+    }
+    variance(&counts).min(best_with_skips + UNPLACED_PENALTY)
+}
+
+fn variance(counts: &[usize]) -> f64 {
+    if counts.is_empty() {
+        return 0.0;
+    }
+    let avg = counts.iter().sum::<usize>() as f64 / counts.len() as f64;
+    counts.iter().map(|&c| (c as f64 - avg).powi(2)).sum()
+}
+
+// End of the time budget for a generation request starting now
+pub fn solver_deadline(config: &SchedulerConfig) -> Instant {
+    Instant::now() + Duration::from_secs(config.solver_time_budget_secs.max(1))
+}
+
+// Deadline for the next of `runs_left` solver runs: an even share of the time left before
+// `deadline`. Time a run does not use passes on to the ones after it.
+pub fn share_of_budget(deadline: Instant, runs_left: usize) -> Instant {
+    let now = Instant::now();
+    now + deadline.saturating_duration_since(now) / runs_left.max(1) as u32
+}
+
+// Searches the full assignment space for the schedule with the lowest `schedule_objective`.
+// The greedy result is the starting incumbent, so the answer is never worse than greedy.
+pub fn solve_exact(
+    generator: &ScheduleGenerator,
+    employees: &[Employee],
+    past_schedules: &PastSchedules,
+    rng: &mut StdRng,
+) -> ScheduleResult {
+    let greedy = generate_schedule(generator, employees, past_schedules, rng);
+    let female_ratio = company_female_ratio(employees);
+    let capped_locations: Vec<(usize, usize)> = generator
+        .constraints
+        .locations
+        .iter()
+        .filter_map(|location| location.capacity.map(|cap| (location.id, cap)))
+        .collect();
+
+    let mut day_counts: DayCount = generator
+        .weekdays
+        .iter()
+        .map(|day| (day.clone(), 0))
+        .collect();
+    let mut base_schedule: MonthlySchedule = generator
+        .weekdays
+        .iter()
+        .map(|day| (day.clone(), Vec::new()))
+        .collect();
+    let (flexible_employees, _fixed_employees) =
+        process_fixed_schedules(generator, employees, &mut day_counts, &mut base_schedule);
+
+    // Employees without any usable combination were already reported by the greedy pass
+    let mut candidates: Vec<Candidate> = flexible_employees
+        .into_iter()
+        .filter_map(|employee| {
+            let required_days = generator.required_days_for(&employee);
+            if required_days == 0 {
+                return None;
+            }
+            let combos =
+                generator.combinations_with(required_days, generator.pinned_days(employee.id));
+            let frequencies = past_day_frequencies(employee.id, past_schedules, &generator.config);
+            let options: Vec<(Vec<usize>, f64)> = combos
+                .iter()
+                .filter(|combo| is_available(&combo.days, &employee))
+                .map(|combo| {
+                    let indices = combo
+                        .days
+                        .iter()
+                        .filter_map(|day| generator.weekdays.iter().position(|d| d == day))
+                        .collect();
+                    let repetition = combo
+                        .days
+                        .iter()
+                        .map(|day| frequencies.get(day).unwrap_or(&0.0))
+                        .sum::<f64>();
+                    let history = generator.config.repetition_weight * repetition
+                        + generator.config.fairness_weight
+                            * fairness_cost(generator, employee.id, &combo.days);
+                    (indices, history)
+                })
+                .collect();
+            if options.is_empty() {
+                return None;
+            }
+            let location = employee.office_location().and_then(|location_id| {
+                capped_locations
+                    .iter()
+                    .position(|(id, _)| *id == location_id)
+            });
+            Some(Candidate {
+                employee,
+                options,
+                required_days,
+                location,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.required_days
+            .cmp(&a.required_days)
+            .then(a.options.len().cmp(&b.options.len()))
+    });
+
+    let mut days_after = vec![0; candidates.len() + 1];
+    let mut history_after = vec![0.0; candidates.len() + 1];
+    for i in (0..candidates.len()).rev() {
+        days_after[i] = days_after[i + 1] + candidates[i].required_days;
+        let min_history = candidates[i]
+            .options
+            .iter()
+            .map(|(_, history)| *history)
+            .fold(f64::INFINITY, f64::min);
+        history_after[i] = history_after[i + 1] + min_history;
+    }
+
+    let counts: Vec<usize> = generator
+        .weekdays
+        .iter()
+        .map(|day| base_schedule.get(day).map_or(0, |emps| emps.len()))
+        .collect();
+    let nsp_counts: Vec<usize> = generator
+        .weekdays
+        .iter()
+        .map(|day| {
+            base_schedule
+                .get(day)
+                .map_or(0, |emps| emps.iter().filter(|e| e.is_nsp).count())
+        })
+        .collect();
+    let capacities = generator
+        .weekdays
+        .iter()
+        .map(|day| generator.config.capacity_for(day))
+        .collect();
+    let location_counts = capped_locations
+        .iter()
+        .map(|(location_id, _)| {
+            generator
+                .weekdays
+                .iter()
+                .map(|day| location_count(&base_schedule, day, *location_id))
+                .collect()
+        })
+        .collect();
+
+    let greedy_unplaced = greedy
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, ScheduleWarning::NoCapacityLeft { .. }))
+        .count();
+    let greedy_score =
+        schedule_objective(generator, &greedy.schedule, past_schedules, female_ratio)
+            + UNPLACED_PENALTY * greedy_unplaced as f64;
+
+    let choice = vec![None; candidates.len()];
+    let mut search = Search {
+        generator,
+        past_schedules,
+        base_schedule: &base_schedule,
+        female_ratio,
+        candidates,
+        capacities,
+        location_capacities: capped_locations.iter().map(|(_, cap)| *cap).collect(),
+        days_after,
+        history_after,
+        counts,
+        nsp_counts,
+        location_counts,
+        choice,
+        best_choice: None,
+        best_score: greedy_score,
+        deadline: generator
+            .constraints
+            .solver_deadline
+            .unwrap_or_else(|| solver_deadline(&generator.config)),
+        nodes: 0,
+        timed_out: false,
+    };
+    search.run(0, 0.0);
+    let proven_optimal = Some(!search.timed_out);
+
+    let Some(best_choice) = search.best_choice.clone() else {
+        // Nothing beat the greedy schedule
+        return ScheduleResult {
+            proven_optimal,
+            ..greedy
+        };
+    };
+
+    let schedule = search.build_schedule(&best_choice);
+
+    // Structural warnings carry over; placement-dependent ones are recomputed
+    let mut warnings: Vec<ScheduleWarning> = greedy
+        .warnings
+        .into_iter()
+        .filter(|w| {
+            matches!(
+                w,
+                ScheduleWarning::UnplacedEmployee { .. }
+                    | ScheduleWarning::NoAvailableCombination { .. }
+                    | ScheduleWarning::DayOverCapacity { .. }
+                    | ScheduleWarning::LocationOverCapacity { .. }
+            )
+        })
+        .collect();
+    for (candidate, picked) in search.candidates.iter().zip(&best_choice) {
+        if picked.is_none() {
+            warnings.push(ScheduleWarning::NoCapacityLeft {
+                employee: candidate.employee.clone(),
+                days: candidate.required_days,
+            });
+        }
+    }
+    check_coverage_rules(generator, &schedule, &mut warnings);
+    check_nsp_policy(generator, &schedule, &mut warnings);
+    check_team_colocation(generator, &schedule, &mut warnings);
+    check_pair_constraints(generator, &schedule, &mut warnings);
+
+    ScheduleResult {
+        schedule,
+        warnings,
+        proven_optimal,
+        explanations: greedy.explanations,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::schema::{ScheduleConstraints, SolverMode, Weekday};
+    use crate::server::test_support::employee;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    const SEED: u64 = 42;
+
+    // Six people with preferences and a month of history, small enough to search fully
+    fn instance() -> (Vec<Employee>, PastSchedules) {
+        let mut employees: Vec<Employee> = (1..=6).map(|id| employee(id, 2)).collect();
+        employees[0].preferred_days = vec![Weekday::Monday, Weekday::Wednesday];
+        employees[1].preferred_days = vec![Weekday::Monday];
+        employees[2].required_days = 3;
+        employees[3].unavailable_days = vec![Weekday::Friday];
+        let past: PastSchedules = employees
+            .iter()
+            .map(|e| {
+                let days: HashSet<Weekday> = [Weekday::Monday, Weekday::Wednesday].into();
+                (e.id, vec![days])
+            })
+            .collect();
+        (employees, past)
+    }
+
+    fn generator(constraints: ScheduleConstraints) -> ScheduleGenerator {
+        let config = SchedulerConfig {
+            default_capacity: Some(4),
+            solver_mode: SolverMode::Exact,
+            ..Default::default()
+        };
+        ScheduleGenerator::new(config, constraints)
+    }
+
+    #[test]
+    fn small_instance_is_proven_optimal() {
+        let (employees, past) = instance();
+        let generator = generator(ScheduleConstraints::default());
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        let result = solve_exact(&generator, &employees, &past, &mut rng);
+
+        assert_eq!(result.proven_optimal, Some(true));
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn exact_objective_is_never_above_greedy() {
+        let (employees, past) = instance();
+        let generator = generator(ScheduleConstraints::default());
+        let female_ratio = company_female_ratio(&employees);
+
+        let greedy = generate_schedule(
+            &generator,
+            &employees,
+            &past,
+            &mut StdRng::seed_from_u64(SEED),
+        );
+        let exact = solve_exact(
+            &generator,
+            &employees,
+            &past,
+            &mut StdRng::seed_from_u64(SEED),
+        );
+
+        let greedy_score = schedule_objective(&generator, &greedy.schedule, &past, female_ratio);
+        let exact_score = schedule_objective(&generator, &exact.schedule, &past, female_ratio);
+        assert!(
+            exact_score <= greedy_score + 1e-9,
+            "exact {} greedy {}",
+            exact_score,
+            greedy_score
+        );
+    }
+
+    #[test]
+    fn spent_budget_is_not_reported_as_optimal() {
+        let (employees, past) = instance();
+        let generator = generator(ScheduleConstraints {
+            solver_deadline: Some(Instant::now()),
+            ..Default::default()
+        });
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        let result = solve_exact(&generator, &employees, &past, &mut rng);
+
+        assert_eq!(result.proven_optimal, Some(false));
+        // The greedy schedule is still returned in full
+        let placed: usize = result.schedule.values().map(|day| day.len()).sum();
+        assert_eq!(placed, 13);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::server::schema::{Employee, Role, Sex};
+
+// Flexible, non-NSP employee with no day preferences, for building test inputs
+pub fn employee(id: usize, required_days: u8) -> Employee {
+    Employee {
+        id,
+        name: format!("Employee {}", id),
+        sex: Sex::Male,
+        role: Role::FullStackEngineer,
+        required_days,
+        fixed_days: Vec::new(),
+        is_nsp: false,
+        preferred_days: Vec::new(),
+        unavailable_days: Vec::new(),
+        team_id: None,
+        location_id: None,
+        attends_location_id: None,
+        desk_needs: Vec::new(),
+        needs_parking: false,
+    }
+}